@@ -39,7 +39,7 @@ fn sc_sp_setup(bencher: &mut Bencher) {
                 || {
                     let mut sum = 0;
                     for _ in 0..NUMBER_OF_ELEMENTS {
-                        sum += queue_cons.dequeue();
+                        sum += queue_cons.dequeue().unwrap();
                     }
                     expect!(sum).to(be_equal_to(expected_result))
                 }
@@ -55,7 +55,7 @@ fn sc_sp_setup(bencher: &mut Bencher) {
             bencher_prod.iter(
                 || {
                     for i in 0..NUMBER_OF_ELEMENTS {
-                        queue_prod.enqueue(i);
+                        queue_prod.enqueue(i).unwrap();
                     }
                 }
             );
@@ -246,7 +246,7 @@ pub fn spawn_consumer<'q, 'b>(queue: &'q ArrayBlockingQueue<i64>, barrier: &'b A
             barrier_cons.wait();
             let mut sum = 0;
             for _ in 0..oper {
-                sum += consume.dequeue();
+                sum += consume.dequeue().unwrap();
             }
             sum
         }
@@ -260,7 +260,7 @@ pub fn spawn_producer<'q, 'b>(queue: &'q ArrayBlockingQueue<i64>, barrier: &'b A
         move || {
             barrier_prod.wait();
             for i in 0..oper {
-                produce.enqueue(i);
+                produce.enqueue(i).unwrap();
             }
         }
     );
@@ -0,0 +1,84 @@
+#![feature(test)]
+
+extern crate concrust;
+extern crate test;
+
+pub use concrust::map::{ConcurrentMap, ConcurrentHashMap, LockFreeHashMap};
+
+pub use std::thread;
+pub use std::sync::{Arc, Barrier};
+
+const NUMBER_OF_KEYS: usize = 1000;
+const NUMBER_OF_THREADS: usize = 4;
+const OPERATIONS_PER_THREAD: usize = 1000;
+
+#[bench]
+fn read_heavy_concurrent_hash_map(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            mixed_load_iter(Arc::new(ConcurrentHashMap::new()), 10);
+        }
+    );
+}
+
+#[bench]
+fn read_heavy_lock_free_hash_map(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            mixed_load_iter(Arc::new(LockFreeHashMap::new()), 10);
+        }
+    );
+}
+
+#[bench]
+fn write_heavy_concurrent_hash_map(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            mixed_load_iter(Arc::new(ConcurrentHashMap::new()), 1);
+        }
+    );
+}
+
+#[bench]
+fn write_heavy_lock_free_hash_map(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            mixed_load_iter(Arc::new(LockFreeHashMap::new()), 1);
+        }
+    );
+}
+
+/// Every thread performs one write (insert or remove) per `reads_per_write` reads
+fn mixed_load_iter<M>(map: Arc<M>, reads_per_write: usize)
+        where M: ConcurrentMap<usize, usize> + Send + Sync + 'static {
+    for key in 0..NUMBER_OF_KEYS {
+        map.insert(key, key);
+    }
+    let barrier = Arc::new(Barrier::new(NUMBER_OF_THREADS + 1));
+    let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+    for id in 0..NUMBER_OF_THREADS {
+        let map = map.clone();
+        let barrier = barrier.clone();
+        results.push(thread::spawn(
+            move || {
+                barrier.wait();
+                for i in 0..OPERATIONS_PER_THREAD {
+                    let key = (id * OPERATIONS_PER_THREAD + i) % NUMBER_OF_KEYS;
+                    if i % (reads_per_write + 1) != 0 {
+                        test::black_box(map.get(&key));
+                    }
+                    else if (i / (reads_per_write + 1)) % 2 == 0 {
+                        map.insert(key, i);
+                    }
+                    else {
+                        map.remove(&key);
+                    }
+                }
+            }
+        ));
+    }
+    barrier.wait();
+    for jh in results {
+        jh.join().unwrap();
+    }
+}
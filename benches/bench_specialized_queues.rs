@@ -0,0 +1,88 @@
+#![feature(test)]
+
+extern crate concrust;
+extern crate test;
+
+pub use concrust::queue::{BlockingQueue, ArrayBlockingQueue, SpscQueue, MpscQueue};
+
+pub use std::thread;
+pub use std::sync::{Arc, Barrier};
+
+const NUMBER_OF_ELEMENTS: i64 = 10000;
+const NUMBER_OF_PRODUCERS: i64 = 4;
+const CAPACITY: usize = 1024;
+
+#[bench]
+fn single_producer_single_consumer_array_blocking_queue(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            let queue: ArrayBlockingQueue<i64> = ArrayBlockingQueue::with_capacity(CAPACITY);
+            assert_eq!(producers_single_consumer_iter(Arc::new(queue), 1), sum(NUMBER_OF_ELEMENTS));
+        }
+    );
+}
+
+#[bench]
+fn single_producer_single_consumer_spsc_queue(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            let queue: SpscQueue<i64> = SpscQueue::spsc(CAPACITY);
+            assert_eq!(producers_single_consumer_iter(Arc::new(queue), 1), sum(NUMBER_OF_ELEMENTS));
+        }
+    );
+}
+
+#[bench]
+fn multiple_producers_single_consumer_array_blocking_queue(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            let queue: ArrayBlockingQueue<i64> = ArrayBlockingQueue::with_capacity(CAPACITY);
+            let expected = sum(NUMBER_OF_ELEMENTS / NUMBER_OF_PRODUCERS) * NUMBER_OF_PRODUCERS;
+            assert_eq!(producers_single_consumer_iter(Arc::new(queue), NUMBER_OF_PRODUCERS), expected);
+        }
+    );
+}
+
+#[bench]
+fn multiple_producers_single_consumer_mpsc_queue(bencher: &mut test::Bencher) {
+    bencher.iter(
+        || {
+            let queue: MpscQueue<i64> = MpscQueue::mpsc();
+            let expected = sum(NUMBER_OF_ELEMENTS / NUMBER_OF_PRODUCERS) * NUMBER_OF_PRODUCERS;
+            assert_eq!(producers_single_consumer_iter(Arc::new(queue), NUMBER_OF_PRODUCERS), expected);
+        }
+    );
+}
+
+/// Every producer enqueues its share of elements, current thread consumes all of them
+fn producers_single_consumer_iter<Q>(queue: Arc<Q>, producers: i64) -> i64
+        where Q: BlockingQueue<i64> + Send + Sync + 'static {
+    let oper = NUMBER_OF_ELEMENTS / producers;
+    let barrier = Arc::new(Barrier::new(producers as usize + 1));
+    let mut handles = Vec::with_capacity(producers as usize);
+    for _ in 0..producers {
+        let queue = queue.clone();
+        let barrier = barrier.clone();
+        handles.push(thread::spawn(
+            move || {
+                barrier.wait();
+                for i in 0..oper {
+                    queue.enqueue(i).unwrap();
+                }
+            }
+        ));
+    }
+    barrier.wait();
+    let mut sum = 0;
+    for _ in 0..oper * producers {
+        sum += queue.dequeue().unwrap();
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    sum
+}
+
+pub fn sum(last: i64) -> i64 {
+    (last - 1) * last / 2
+}
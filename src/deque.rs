@@ -0,0 +1,263 @@
+//! Work-stealing deque of Chase and Lev
+//!
+//! `Worker` is owned by one thread which pushes and pops values at the bottom
+//! in LIFO order, any number of `Stealer`s take values from the top in FIFO order.
+//! Owner synchronizes with stealers only when the deque is almost empty, so it is
+//! a building block of schedulers where every thread keeps its own tasks and
+//! steals from others when it runs out of work
+
+extern crate alloc;
+
+use self::alloc::raw_vec::RawVec;
+use std::ptr;
+use std::mem;
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicIsize, AtomicPtr, Ordering};
+use std::sync::atomic;
+
+use super::reclaim::{Reclaim, Protect, Epoch};
+
+const MIN_CAPACITY: usize = 16;
+
+/// Hazard slot used by stealers to protect buffer
+const BUFFER: usize = 0;
+
+/// Circular buffer which does not own its values, deque decides which of them are alive
+struct Buffer<T> {
+    mask: usize,
+    data: RawVec<T>
+}
+
+impl <T> Buffer<T> {
+
+    fn with_capacity(capacity: usize) -> Buffer<T> {
+        Buffer {
+            mask: capacity - 1,
+            data: RawVec::with_capacity(capacity)
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn at(&self, index: isize) -> *mut T {
+        unsafe { self.data.ptr().offset((index as usize & self.mask) as isize) }
+    }
+
+    unsafe fn write(&self, index: isize, val: T) {
+        ptr::write(self.at(index), val)
+    }
+
+    unsafe fn read(&self, index: isize) -> T {
+        ptr::read(self.at(index))
+    }
+
+    /// Copy values between top and bottom into buffer of twice capacity
+    unsafe fn grow(&self, top: isize, bottom: isize) -> Buffer<T> {
+        let buffer = Buffer::with_capacity(self.capacity() * 2);
+        for index in top..bottom {
+            ptr::copy_nonoverlapping(self.at(index), buffer.at(index), 1);
+        }
+        buffer
+    }
+}
+
+struct Deque<T, R> {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    reclaim: PhantomData<R>
+}
+
+unsafe impl <T: Send, R> Send for Deque<T, R> { }
+unsafe impl <T: Send, R> Sync for Deque<T, R> { }
+
+impl <T, R> Deque<T, R> {
+
+    fn len(&self) -> usize {
+        let top = self.top.load(Ordering::Acquire);
+        let bottom = self.bottom.load(Ordering::Acquire);
+        if bottom > top {
+            (bottom - top) as usize
+        }
+        else {
+            0
+        }
+    }
+}
+
+impl <T, R> Drop for Deque<T, R> {
+
+    fn drop(&mut self) {
+        let top = self.top.load(Ordering::Relaxed);
+        let bottom = self.bottom.load(Ordering::Relaxed);
+        let buffer = unsafe { Box::from_raw(self.buffer.load(Ordering::Relaxed)) };
+        for index in top..bottom {
+            unsafe { drop(buffer.read(index)); }
+        }
+    }
+}
+
+/// Result of steal operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Steal<T> {
+    /// Deque was empty
+    Empty,
+    /// Value was stolen
+    Success(T),
+    /// Another thread took the value first, steal could be retried
+    Retry
+}
+
+/// Owner side of deque, pushes and pops values at the bottom
+///
+/// Worker could be sent to another thread but could not be shared between threads.
+/// Buffers replaced when deque grows are destroyed by reclamation scheme `R`
+pub struct Worker<T, R = Epoch> {
+    deque: Arc<Deque<T, R>>,
+    owner: PhantomData<Cell<()>>
+}
+
+/// Thief side of deque, steals values from the top, could be cloned and shared between threads
+pub struct Stealer<T, R = Epoch> {
+    deque: Arc<Deque<T, R>>
+}
+
+impl <T: Send + 'static, R: Reclaim> Worker<T, R> {
+
+    /// Create empty deque
+    pub fn new() -> Worker<T, R> {
+        let buffer = Box::into_raw(Box::new(Buffer::with_capacity(MIN_CAPACITY)));
+        Worker {
+            deque: Arc::new(Deque {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(buffer),
+                reclaim: PhantomData
+            }),
+            owner: PhantomData
+        }
+    }
+
+    /// Create stealer of current deque
+    pub fn stealer(&self) -> Stealer<T, R> {
+        Stealer {
+            deque: self.deque.clone()
+        }
+    }
+
+    /// Return number of values in deque, it could be outdated when it is returned
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    /// Check if deque is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push value at the bottom, buffer grows when it is full
+    pub fn push(&self, val: T) {
+        let deque = &*self.deque;
+        let bottom = deque.bottom.load(Ordering::Relaxed);
+        let top = deque.top.load(Ordering::Acquire);
+        // only owner replaces buffer
+        let mut buffer = deque.buffer.load(Ordering::Relaxed);
+        unsafe {
+            if (bottom - top) as usize >= (*buffer).capacity() {
+                buffer = self.grow(buffer, top, bottom);
+            }
+            (*buffer).write(bottom, val);
+        }
+        deque.bottom.store(bottom + 1, Ordering::Release);
+    }
+
+    /// Pop value from the bottom, races with stealers only for the last value
+    pub fn pop(&self) -> Option<T> {
+        let deque = &*self.deque;
+        let bottom = deque.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = deque.buffer.load(Ordering::Relaxed);
+        deque.bottom.store(bottom, Ordering::Relaxed);
+        // stealers have to see the decreased bottom before owner reads top
+        atomic::fence(Ordering::SeqCst);
+        let top = deque.top.load(Ordering::Relaxed);
+        if top > bottom {
+            deque.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+        let val = unsafe { (*buffer).read(bottom) };
+        if top < bottom {
+            return Some(val);
+        }
+        let won = deque.top.compare_and_swap(top, top + 1, Ordering::SeqCst) == top;
+        deque.bottom.store(bottom + 1, Ordering::Relaxed);
+        if won {
+            Some(val)
+        }
+        else {
+            // stealer took the value
+            mem::forget(val);
+            None
+        }
+    }
+
+    /// Replace buffer by buffer of twice capacity and retire the old one
+    unsafe fn grow(&self, buffer: *mut Buffer<T>, top: isize, bottom: isize) -> *mut Buffer<T> {
+        let grown = Box::into_raw(Box::new((*buffer).grow(top, bottom)));
+        let guard = R::guard();
+        self.deque.buffer.store(grown, Ordering::Release);
+        // stealers could still read values from the old buffer
+        guard.retire(buffer);
+        grown
+    }
+}
+
+impl <T, R: Reclaim> Stealer<T, R> {
+
+    /// Return number of values in deque, it could be outdated when it is returned
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    /// Check if deque is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Steal value from the top
+    pub fn steal(&self) -> Steal<T> {
+        let deque = &*self.deque;
+        let top = deque.top.load(Ordering::Acquire);
+        // pairs with fence of pop, so that stealer and owner could not both take the last value
+        atomic::fence(Ordering::SeqCst);
+        let bottom = deque.bottom.load(Ordering::Acquire);
+        if top >= bottom {
+            return Steal::Empty;
+        }
+        let guard = R::guard();
+        let buffer = guard.protect_ptr(BUFFER, &deque.buffer);
+        let val = unsafe { (*buffer).read(top) };
+        if deque.top.compare_and_swap(top, top + 1, Ordering::SeqCst) == top {
+            Steal::Success(val)
+        }
+        else {
+            // value belongs to the thread which moved top
+            mem::forget(val);
+            Steal::Retry
+        }
+    }
+}
+
+impl <T, R> Clone for Stealer<T, R> {
+
+    fn clone(&self) -> Stealer<T, R> {
+        Stealer {
+            deque: self.deque.clone()
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Epoch-based memory reclamation
+//!
+//! A thread has to be pinned with `pin()` while it reads shared pointers of lock-free
+//! structures. Nodes unlinked from a structure are handed to `Guard::defer_destroy` and
+//! destroyed only after every thread that was pinned at the moment of unlinking unpins.
+//!
+//! The global epoch advances when all pinned threads have observed the current one,
+//! garbage retired in epoch `e` is destroyed once the global epoch reaches `e + 2`.
+//! A thread that stays pinned for a long time stops the epoch from advancing,
+//! so garbage of all threads grows until it unpins
+
+use std::ptr;
+
+use std::cell::{Cell, RefCell};
+
+use std::marker::PhantomData;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::atomic;
+
+/// Lowest bit of participant state is set while the thread is pinned
+const PINNED: usize = 1;
+
+/// Every that many pins a thread tries to advance global epoch and collect its garbage
+const COLLECT_FREQUENCY: usize = 128;
+
+/// Thread tries to collect garbage when it retires that many objects
+const GARBAGE_THRESHOLD: usize = 64;
+
+static EPOCH: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Head of the list of registered threads, entries are reused but never freed
+static PARTICIPANTS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Stack of garbage bags left by exited threads
+static ORPHANS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+struct Participant {
+    state: AtomicUsize,
+    in_use: AtomicBool,
+    next: *mut Participant
+}
+
+impl Participant {
+
+    /// Take entry of exited thread or register a new one
+    fn acquire() -> &'static Participant {
+        let mut current = PARTICIPANTS.load(Ordering::Acquire) as *mut Participant;
+        while !current.is_null() {
+            let participant = unsafe { &*current };
+            if !participant.in_use.load(Ordering::Relaxed) && !participant.in_use.swap(true, Ordering::Acquire) {
+                return participant;
+            }
+            current = participant.next;
+        }
+        let participant = Box::into_raw(Box::new(Participant {
+            state: AtomicUsize::new(0),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut()
+        }));
+        loop {
+            let head = PARTICIPANTS.load(Ordering::Acquire);
+            unsafe { (*participant).next = head as *mut Participant; }
+            if PARTICIPANTS.compare_and_swap(head, participant as usize, Ordering::AcqRel) == head {
+                return unsafe { &*participant };
+            }
+        }
+    }
+}
+
+/// Object waiting for destruction together with epoch in which it was retired
+struct Deferred {
+    epoch: usize,
+    ptr: *mut u8,
+    destroy: unsafe fn(*mut u8)
+}
+
+impl Deferred {
+
+    fn is_expired(&self, epoch: usize) -> bool {
+        epoch.wrapping_sub(self.epoch) >= 2
+    }
+}
+
+unsafe fn destroy<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+struct Bag {
+    garbage: Vec<Deferred>,
+    next: *mut Bag
+}
+
+struct Local {
+    participant: &'static Participant,
+    pins: Cell<usize>,
+    count: Cell<usize>,
+    garbage: RefCell<Vec<Deferred>>
+}
+
+impl Local {
+
+    fn new() -> Local {
+        Local {
+            participant: Participant::acquire(),
+            pins: Cell::new(0),
+            count: Cell::new(0),
+            garbage: RefCell::new(Vec::new())
+        }
+    }
+
+    fn pin(&self) {
+        let pins = self.pins.get();
+        self.pins.set(pins + 1);
+        if pins > 0 {
+            return;
+        }
+        let epoch = EPOCH.load(Ordering::Relaxed);
+        self.participant.state.store((epoch << 1) | PINNED, Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+        let count = self.count.get() + 1;
+        self.count.set(count);
+        if count % COLLECT_FREQUENCY == 0 {
+            self.collect();
+        }
+    }
+
+    fn unpin(&self) {
+        let pins = self.pins.get() - 1;
+        self.pins.set(pins);
+        if pins == 0 {
+            self.participant.state.store(0, Ordering::Release);
+        }
+    }
+
+    fn defer(&self, ptr: *mut u8, destroy: unsafe fn(*mut u8)) {
+        // the object is unlinked before the epoch is read, so threads pinned
+        // in later epochs could not reach it
+        atomic::fence(Ordering::SeqCst);
+        let epoch = EPOCH.load(Ordering::Relaxed);
+        let size = {
+            let mut garbage = self.garbage.borrow_mut();
+            garbage.push(Deferred { epoch: epoch, ptr: ptr, destroy: destroy });
+            garbage.len()
+        };
+        if size >= GARBAGE_THRESHOLD {
+            self.collect();
+        }
+    }
+
+    /// Try to advance global epoch and destroy expired garbage of current thread
+    /// and of exited threads
+    fn collect(&self) {
+        let epoch = try_advance();
+        let mut expired = Vec::new();
+        {
+            let mut garbage = self.garbage.borrow_mut();
+            adopt_orphans(&mut garbage);
+            let mut index = 0;
+            while index < garbage.len() {
+                if garbage[index].is_expired(epoch) {
+                    expired.push(garbage.swap_remove(index));
+                }
+                else {
+                    index += 1;
+                }
+            }
+        }
+        // destructors could retire objects of their own
+        for deferred in expired {
+            unsafe { (deferred.destroy)(deferred.ptr); }
+        }
+    }
+}
+
+impl Drop for Local {
+
+    fn drop(&mut self) {
+        self.collect();
+        let garbage = self.garbage.borrow_mut().split_off(0);
+        if !garbage.is_empty() {
+            let bag = Box::into_raw(Box::new(Bag { garbage: garbage, next: ptr::null_mut() }));
+            loop {
+                let head = ORPHANS.load(Ordering::Acquire);
+                unsafe { (*bag).next = head as *mut Bag; }
+                if ORPHANS.compare_and_swap(head, bag as usize, Ordering::AcqRel) == head {
+                    break;
+                }
+            }
+        }
+        self.participant.state.store(0, Ordering::Release);
+        self.participant.in_use.store(false, Ordering::Release);
+    }
+}
+
+thread_local!(static LOCAL: Local = Local::new());
+
+/// Advance global epoch if all pinned threads have observed it, return global epoch
+fn try_advance() -> usize {
+    let epoch = EPOCH.load(Ordering::Relaxed);
+    atomic::fence(Ordering::SeqCst);
+    let mut current = PARTICIPANTS.load(Ordering::Acquire) as *mut Participant;
+    while !current.is_null() {
+        let participant = unsafe { &*current };
+        let state = participant.state.load(Ordering::Relaxed);
+        if state & PINNED == PINNED && state >> 1 != epoch {
+            return epoch;
+        }
+        current = participant.next;
+    }
+    atomic::fence(Ordering::Acquire);
+    let previous = EPOCH.compare_and_swap(epoch, epoch + 1, Ordering::Release);
+    if previous == epoch { epoch + 1 } else { previous }
+}
+
+/// Move garbage left by exited threads into specified bag
+fn adopt_orphans(garbage: &mut Vec<Deferred>) {
+    let mut bag = ORPHANS.swap(0, Ordering::Acquire) as *mut Bag;
+    while !bag.is_null() {
+        let mut current = unsafe { Box::from_raw(bag) };
+        garbage.append(&mut current.garbage);
+        bag = current.next;
+    }
+}
+
+/// Pin current thread until returned guard is dropped
+///
+/// Pointers loaded from lock-free structures stay valid while the thread is pinned.
+/// Guards could be nested, the thread is unpinned when the outermost guard is dropped
+pub fn pin() -> Guard {
+    LOCAL.with(|local| local.pin());
+    Guard {
+        _marker: PhantomData
+    }
+}
+
+/// RAII guard which keeps current thread pinned
+pub struct Guard {
+    _marker: PhantomData<*mut ()>
+}
+
+impl Guard {
+
+    /// Destroy boxed object when no pinned thread could hold reference to it
+    ///
+    /// The object could be destroyed by another thread and after the structure
+    /// it belonged to is dropped
+    ///
+    /// # Safety
+    ///
+    /// The pointer has to be obtained from `Box::into_raw` and to be unreachable
+    /// for threads which pin after this call
+    pub unsafe fn defer_destroy<T: Send + 'static>(&self, ptr: *mut T) {
+        LOCAL.with(|local| local.defer(ptr as *mut u8, destroy::<T>));
+    }
+
+    /// Try to advance global epoch and destroy garbage retired by current thread
+    /// which is not reachable anymore
+    pub fn flush(&self) {
+        LOCAL.with(|local| local.collect());
+    }
+}
+
+impl Drop for Guard {
+
+    fn drop(&mut self) {
+        LOCAL.with(|local| local.unpin());
+    }
+}
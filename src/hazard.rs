@@ -0,0 +1,310 @@
+//! Hazard pointer memory reclamation
+//!
+//! Before a thread dereferences a shared pointer it publishes the pointer in one of
+//! its hazard slots. Retired objects are destroyed only when no slot of any thread
+//! holds them. Unlike epochs a thread that is parked while holding hazards keeps alive
+//! only the objects it protects, so every thread keeps at most a bounded number
+//! of retired objects which could not be destroyed yet
+
+use std::ptr;
+use std::cmp;
+
+use std::cell::{Cell, RefCell};
+
+use std::marker::PhantomData;
+
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::sync::atomic;
+
+/// Number of hazard slots available through one guard
+pub const SLOTS: usize = 4;
+
+/// Number of guards which could be alive in one thread at the same time
+const GUARDS_PER_THREAD: usize = 4;
+
+/// Lowest bit of protected links is used as a mark by lock-free lists
+const MARK: usize = 1;
+
+/// Retired objects are scanned at least when a thread retires that many of them
+const MIN_THRESHOLD: usize = 64;
+
+/// Head of the list of hazard records, records are reused but never freed
+static RECORDS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+static NUMBER_OF_RECORDS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+/// Stack of retired lists left by exited threads
+static ORPHANS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+struct Record {
+    hazards: Vec<AtomicUsize>,
+    in_use: AtomicBool,
+    next: *mut Record
+}
+
+impl Record {
+
+    /// Take record of exited thread or register a new one
+    fn acquire() -> &'static Record {
+        let mut current = RECORDS.load(Ordering::Acquire) as *mut Record;
+        while !current.is_null() {
+            let record = unsafe { &*current };
+            if !record.in_use.load(Ordering::Relaxed) && !record.in_use.swap(true, Ordering::Acquire) {
+                return record;
+            }
+            current = record.next;
+        }
+        let mut hazards = Vec::with_capacity(SLOTS * GUARDS_PER_THREAD);
+        for _ in 0..SLOTS * GUARDS_PER_THREAD {
+            hazards.push(AtomicUsize::new(0));
+        }
+        let record = Box::into_raw(Box::new(Record {
+            hazards: hazards,
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut()
+        }));
+        NUMBER_OF_RECORDS.fetch_add(1, Ordering::Relaxed);
+        loop {
+            let head = RECORDS.load(Ordering::Acquire);
+            unsafe { (*record).next = head as *mut Record; }
+            if RECORDS.compare_and_swap(head, record as usize, Ordering::AcqRel) == head {
+                return unsafe { &*record };
+            }
+        }
+    }
+}
+
+struct Retired {
+    ptr: *mut u8,
+    destroy: unsafe fn(*mut u8)
+}
+
+unsafe fn destroy<T>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+struct Orphans {
+    retired: Vec<Retired>,
+    next: *mut Orphans
+}
+
+struct Local {
+    record: &'static Record,
+    guards: Cell<usize>,
+    retired: RefCell<Vec<Retired>>
+}
+
+impl Local {
+
+    fn new() -> Local {
+        Local {
+            record: Record::acquire(),
+            guards: Cell::new(0),
+            retired: RefCell::new(Vec::new())
+        }
+    }
+
+    /// Take free block of hazard slots
+    fn acquire_block(&self) -> usize {
+        let guards = self.guards.get();
+        for block in 0..GUARDS_PER_THREAD {
+            if guards & (1 << block) == 0 {
+                self.guards.set(guards | (1 << block));
+                return block * SLOTS;
+            }
+        }
+        panic!("more than {} hazard guards are alive in current thread", GUARDS_PER_THREAD);
+    }
+
+    fn release_block(&self, base: usize) {
+        for slot in base..base + SLOTS {
+            self.record.hazards[slot].store(0, Ordering::Release);
+        }
+        self.guards.set(self.guards.get() & !(1 << (base / SLOTS)));
+    }
+
+    fn retire(&self, ptr: *mut u8, destroy: unsafe fn(*mut u8)) {
+        let size = {
+            let mut retired = self.retired.borrow_mut();
+            retired.push(Retired { ptr: ptr, destroy: destroy });
+            retired.len()
+        };
+        if size >= threshold() {
+            self.scan();
+        }
+    }
+
+    /// Destroy retired objects of current thread and of exited threads
+    /// which are not protected by any hazard
+    fn scan(&self) {
+        // orphans are adopted before hazards are read, otherwise an orphan could be
+        // retired after the snapshot and destroyed while a hazard published after it protects it
+        adopt_orphans(&mut self.retired.borrow_mut());
+        atomic::fence(Ordering::SeqCst);
+        let mut hazards = Vec::new();
+        let mut current = RECORDS.load(Ordering::Acquire) as *mut Record;
+        while !current.is_null() {
+            let record = unsafe { &*current };
+            for hazard in &record.hazards {
+                let hazard = hazard.load(Ordering::Acquire);
+                if hazard != 0 {
+                    hazards.push(hazard);
+                }
+            }
+            current = record.next;
+        }
+        hazards.sort();
+        let mut expired = Vec::new();
+        {
+            let mut retired = self.retired.borrow_mut();
+            let mut index = 0;
+            while index < retired.len() {
+                if hazards.binary_search(&(retired[index].ptr as usize)).is_err() {
+                    expired.push(retired.swap_remove(index));
+                }
+                else {
+                    index += 1;
+                }
+            }
+        }
+        // destructors could retire objects of their own
+        for retired in expired {
+            unsafe { (retired.destroy)(retired.ptr); }
+        }
+    }
+}
+
+impl Drop for Local {
+
+    fn drop(&mut self) {
+        for hazard in &self.record.hazards {
+            hazard.store(0, Ordering::Release);
+        }
+        self.scan();
+        let retired = self.retired.borrow_mut().split_off(0);
+        if !retired.is_empty() {
+            let orphans = Box::into_raw(Box::new(Orphans { retired: retired, next: ptr::null_mut() }));
+            loop {
+                let head = ORPHANS.load(Ordering::Acquire);
+                unsafe { (*orphans).next = head as *mut Orphans; }
+                if ORPHANS.compare_and_swap(head, orphans as usize, Ordering::AcqRel) == head {
+                    break;
+                }
+            }
+        }
+        self.record.in_use.store(false, Ordering::Release);
+    }
+}
+
+thread_local!(static LOCAL: Local = Local::new());
+
+/// Number of retired objects after which thread scans hazards,
+/// it is proportional to number of hazard slots so that every scan destroys
+/// at least half of retired objects
+fn threshold() -> usize {
+    cmp::max(2 * SLOTS * GUARDS_PER_THREAD * NUMBER_OF_RECORDS.load(Ordering::Relaxed), MIN_THRESHOLD)
+}
+
+fn adopt_orphans(retired: &mut Vec<Retired>) {
+    let mut orphans = ORPHANS.swap(0, Ordering::Acquire) as *mut Orphans;
+    while !orphans.is_null() {
+        let mut current = unsafe { Box::from_raw(orphans) };
+        retired.append(&mut current.retired);
+        orphans = current.next;
+    }
+}
+
+/// Take block of `SLOTS` hazard slots of current thread
+/// which are cleared when returned guard is dropped
+///
+/// # Panics
+///
+/// Panics if current thread already holds four guards
+pub fn guard() -> Guard {
+    LOCAL.with(
+        |local| Guard {
+            record: local.record,
+            base: local.acquire_block(),
+            _marker: PhantomData
+        }
+    )
+}
+
+/// RAII guard of hazard slots of current thread
+pub struct Guard {
+    record: &'static Record,
+    base: usize,
+    _marker: PhantomData<*mut ()>
+}
+
+impl Guard {
+
+    /// Load link and publish pointer it holds in specified slot, the load is repeated
+    /// until the link holds published pointer. Lowest bit of the link is treated as a mark
+    /// and is not published. Pointed object is not destroyed until the slot is changed
+    pub fn protect(&self, slot: usize, link: &AtomicUsize) -> usize {
+        let mut value = link.load(Ordering::Acquire);
+        loop {
+            self.set(slot, value);
+            atomic::fence(Ordering::SeqCst);
+            let current = link.load(Ordering::Acquire);
+            if current == value {
+                return value;
+            }
+            value = current;
+        }
+    }
+
+    /// Load pointer and publish it in specified slot, the load is repeated
+    /// until the link holds published pointer
+    pub fn protect_ptr<T>(&self, slot: usize, link: &AtomicPtr<T>) -> *mut T {
+        let mut value = link.load(Ordering::Acquire);
+        loop {
+            self.set(slot, value as usize);
+            atomic::fence(Ordering::SeqCst);
+            let current = link.load(Ordering::Acquire);
+            if current == value {
+                return value;
+            }
+            value = current;
+        }
+    }
+
+    /// Publish pointer in specified slot, the caller has to check that
+    /// the pointer is still reachable after it is published
+    pub fn set(&self, slot: usize, ptr: usize) {
+        assert!(slot < SLOTS, "hazard slot should be less than {} but was {}", SLOTS, slot);
+        self.record.hazards[self.base + slot].store(ptr & !MARK, Ordering::Release);
+    }
+
+    /// Stop protecting pointer published in specified slot
+    pub fn clear(&self, slot: usize) {
+        self.set(slot, 0);
+    }
+
+    /// Destroy boxed object when it is not published in any hazard slot
+    ///
+    /// The object could be destroyed by another thread and after the structure
+    /// it belonged to is dropped
+    ///
+    /// # Safety
+    ///
+    /// The pointer has to be obtained from `Box::into_raw` and to be unreachable
+    /// for threads which load pointers after this call
+    pub unsafe fn retire<T: Send + 'static>(&self, ptr: *mut T) {
+        LOCAL.with(|local| local.retire(ptr as *mut u8, destroy::<T>));
+    }
+
+    /// Destroy retired objects of current thread which are not protected
+    pub fn flush(&self) {
+        LOCAL.with(|local| local.scan());
+    }
+}
+
+impl Drop for Guard {
+
+    fn drop(&mut self) {
+        let base = self.base;
+        LOCAL.with(|local| local.release_block(base));
+    }
+}
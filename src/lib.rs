@@ -3,6 +3,13 @@
 
 #![plugin(clippy)]
 
+extern crate crossbeam;
+extern crate num_cpus;
+
+pub mod epoch;
+pub mod hazard;
+pub mod reclaim;
+pub mod deque;
 pub mod primitives;
 pub mod queue;
 pub mod map;
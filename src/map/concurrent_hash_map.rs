@@ -1,126 +1,294 @@
+use std::mem;
 use std::ptr;
+use std::cmp;
+use std::usize;
 
-use std::marker::Copy;
-use std::clone::Clone;
+use std::borrow::Borrow;
 
-use std::ops::Deref;
-use std::ops::DerefMut;
+use std::hash::{Hash, Hasher, BuildHasher};
+use std::collections::hash_map::RandomState;
 
-use std::sync::{RwLock, RwLockWriteGuard};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::ops::{Deref, DerefMut};
+
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
 
 use std::fmt::{Debug, Formatter, Result};
 
+use crossbeam;
+use num_cpus;
+
+use super::{ConcurrentMap, spread};
 use super::super::round_up_to_next_highest_power_of_two;
+use super::super::epoch::{self, Guard};
 
-struct Bucket {
-    key: Option<i32>,
-    value: Option<i32>,
-    next: Option<Link>
+struct Bucket<K, V> {
+    hash: usize,
+    key: K,
+    value: V,
+    next: Link<K, V>
 }
 
-impl Bucket {
+impl <K, V> Bucket<K, V> {
 
-    fn empty() -> Bucket {
-        Bucket {
-            key: None,
-            value: None,
-            next: None
-        }
-    }
-    
-    fn new(key: i32, value: i32) -> Bucket {
+    fn new(hash: usize, key: K, value: V, next: Link<K, V>) -> Bucket<K, V> {
         Bucket {
-            key: Some(key),
-            value: Some(value),
-            next: None
+            hash: hash,
+            key: key,
+            value: value,
+            next: next
         }
     }
 }
 
-impl Debug for Bucket {
+impl <K: Debug, V: Debug> Debug for Bucket<K, V> {
 
     fn fmt(&self, fmt: &mut Formatter) -> Result {
         write!(fmt, "[ Key = {:?} Value = {:?} ]", self.key, self.value)
     }
 }
 
-struct Link {
+type Link<K, V> = Option<Box<Bucket<K, V>>>;
 
-    ptr: *mut Bucket
+/// Head of buckets chain guarded by one lock.
+/// Once all buckets were moved into next table during resize
+/// the bin is marked as moved and all operations continue in next table
+struct Bin<K, V> {
+    link: Link<K, V>,
+    moved: bool
 }
 
-impl Link {
+impl <K, V> Bin<K, V> {
+
+    fn empty() -> Bin<K, V> {
+        Bin {
+            link: None,
+            moved: false
+        }
+    }
+}
+
+struct Table<K, V> {
+    bins: Vec<RwLock<Bin<K, V>>>,
+    threshold: usize,
+    next: AtomicPtr<Table<K, V>>,
+    transfer_index: AtomicUsize,
+    transferred: AtomicUsize
+}
+
+impl <K, V> Table<K, V> {
+
+    fn new(capacity: usize, load_factor: f32) -> Table<K, V> {
+        let mut bins = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            bins.push(RwLock::new(Bin::empty()));
+        }
+        let threshold = if capacity < MAX_CAPACITY {
+            (capacity as f32 * load_factor) as usize
+        }
+        else {
+            usize::MAX
+        };
+        Table {
+            bins: bins,
+            threshold: threshold,
+            next: AtomicPtr::new(ptr::null_mut()),
+            transfer_index: AtomicUsize::new(0),
+            transferred: AtomicUsize::new(0)
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    fn bin(&self, hash: usize) -> &RwLock<Bin<K, V>> {
+        &self.bins[hash & (self.len() - 1)]
+    }
+
+    fn next_table(&self) -> &Table<K, V> {
+        unsafe { &*self.next.load(Ordering::Acquire) }
+    }
 
-    fn new(bucket: Bucket) -> Link {
-        Link {
-            ptr: Box::into_raw(Box::new(bucket))
+    /// Return table into which current table is resizing
+    /// the first thread that starts resize allocates it
+    fn next_table_or_create(&self, load_factor: f32) -> &Table<K, V> {
+        let next = self.next.load(Ordering::Acquire);
+        if !next.is_null() {
+            return unsafe { &*next };
+        }
+        let created = Box::into_raw(Box::new(Table::new(self.len() << 1, load_factor)));
+        let previous = self.next.compare_and_swap(ptr::null_mut(), created, Ordering::AcqRel);
+        if previous.is_null() {
+            unsafe { &*created }
+        }
+        else {
+            unsafe { drop(Box::from_raw(created)); }
+            unsafe { &*previous }
         }
     }
+
+    /// Move bin at specified index into next table splitting its chain
+    /// between bins with the same index and the index shifted on current capacity
+    fn move_bin(&self, index: usize, next: &Table<K, V>) {
+        let capacity = self.len();
+        let mut old = self.bins[index].write().unwrap();
+        let mut low = next.bins[index].write().unwrap();
+        let mut high = next.bins[index + capacity].write().unwrap();
+        let mut link = old.link.take();
+        while let Some(mut bucket) = link {
+            link = bucket.next.take();
+            let target = if bucket.hash & capacity == 0 { &mut low } else { &mut high };
+            bucket.next = target.link.take();
+            target.link = Some(bucket);
+        }
+        old.moved = true;
+    }
 }
 
-impl Deref for Link {
-    type Target = Bucket;
+impl <K, V> Drop for Table<K, V> {
 
-    fn deref(&self) -> &Bucket {
-        unsafe { &*self.ptr }
+    fn drop(&mut self) {
+        // unlink chains one bucket at a time so that long chains
+        // do not overflow stack with recursive drop of boxes
+        for lock in &mut self.bins {
+            let mut link = lock.get_mut().unwrap().link.take();
+            while let Some(mut bucket) = link {
+                link = bucket.next.take();
+            }
+        }
     }
 }
 
-impl DerefMut for Link {
+/// Table whose bins were all moved into next table. It holds no keys and values,
+/// so it is retired without them and could be destroyed by any thread
+struct MovedTable {
+    table: *mut u8,
+    destroy: unsafe fn(*mut u8)
+}
+
+unsafe impl Send for MovedTable { }
 
-    fn deref_mut(&mut self) -> &mut Bucket {
-        unsafe { &mut *self.ptr }
+impl MovedTable {
+
+    fn new<K, V>(table: *mut Table<K, V>) -> MovedTable {
+        MovedTable {
+            table: table as *mut u8,
+            destroy: destroy_table::<K, V>
+        }
     }
 }
 
-impl Clone for Link{
+impl Drop for MovedTable {
 
-    fn clone(&self) -> Link {
-        Link { ptr: self.ptr }
+    fn drop(&mut self) {
+        unsafe { (self.destroy)(self.table) }
     }
 }
 
-impl Copy for Link { }
-unsafe impl Send for Link { }
+unsafe fn destroy_table<K, V>(table: *mut u8) {
+    drop(Box::from_raw(table as *mut Table<K, V>));
+}
+
+const DEFAULT_CAPACITY: usize = 16;
+const DEFAULT_LOAD_FACTOR: f32 = 0.75;
+const MAX_CAPACITY: usize = 1 << 30;
+const TRANSFER_STRIDE: usize = 16;
 
 /// A hash table supporting concurrency for insertions and deletions
 ///
-/// Currnet implementation is non resizeble vector of Read-Write locks-buckets
+/// Currnet implementation is vector of Read-Write locks-buckets
 /// which resolve hash collisions with link to the next key value pair
-pub struct ConcurrentHashMap {
-    table: Vec<RwLock<Link>>,
-    size: AtomicUsize
+///
+/// When number of entries exceeds capacity multiplied by load factor the table
+/// is resized into twice larger one. Like in Java's `ConcurrentHashMap` buckets are
+/// transferred one by one and marked as moved, so readers and writers wait only for
+/// the bucket being moved and writers help to transfer the rest of buckets.
+/// Replaced tables are destroyed through epoch based reclamation, so every operation
+/// pins current thread while it uses the table
+///
+/// All operations take `&self` so the map could be shared between threads with `Arc`
+///
+/// By default keys are hashed with `RandomState` which is resistant to HashDoS attacks,
+/// other hashing algorithm could be plugged in with `with_hasher` constructors
+pub struct ConcurrentHashMap<K, V, S = RandomState> {
+    table: AtomicPtr<Table<K, V>>,
+    size: AtomicUsize,
+    load_factor: f32,
+    hash_builder: S
 }
 
-impl Default for ConcurrentHashMap {
+unsafe impl <K: Send, V: Send, S: Send> Send for ConcurrentHashMap<K, V, S> { }
+unsafe impl <K: Send + Sync, V: Send + Sync, S: Sync> Sync for ConcurrentHashMap<K, V, S> { }
+
+impl <K: Hash + Eq, V, S: BuildHasher + Default> Default for ConcurrentHashMap<K, V, S> {
 
-    fn default() -> ConcurrentHashMap {
-        ConcurrentHashMap::new()
+    fn default() -> ConcurrentHashMap<K, V, S> {
+        ConcurrentHashMap::with_hasher(Default::default())
     }
 }
 
-impl ConcurrentHashMap {
-    
+impl <K: Hash + Eq, V> ConcurrentHashMap<K, V, RandomState> {
+
     /// Create hash table with vector of locks-buckets with default size which is 16
-    pub fn new() -> ConcurrentHashMap {
-        ConcurrentHashMap::with_capacity(16)
+    pub fn new() -> ConcurrentHashMap<K, V, RandomState> {
+        ConcurrentHashMap::with_capacity(DEFAULT_CAPACITY)
     }
 
     /// Create hash table with vector of locks-buckets with specified capacity which will be
     /// increase if needed to next highest power of two
-    pub fn with_capacity(capacity: usize) -> ConcurrentHashMap {
-        let capacity = round_up_to_next_highest_power_of_two(capacity);
-        let mut table = Vec::with_capacity(capacity);
-        for _ in 0..capacity {
-            table.push(RwLock::new(Link::new(Bucket::empty())));
-        }
+    pub fn with_capacity(capacity: usize) -> ConcurrentHashMap<K, V, RandomState> {
+        ConcurrentHashMap::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+
+    /// Create hash table with specified capacity and load factor
+    /// which defines how full the table could be before it is resized
+    pub fn with_capacity_and_load_factor(capacity: usize, load_factor: f32) -> ConcurrentHashMap<K, V, RandomState> {
+        ConcurrentHashMap::with_capacity_load_factor_and_hasher(capacity, load_factor, RandomState::new())
+    }
+}
+
+impl <K: Hash + Eq, V, S: BuildHasher> ConcurrentHashMap<K, V, S> {
+
+    /// Create hash table with default capacity which will use
+    /// specified hash builder to hash keys
+    pub fn with_hasher(hash_builder: S) -> ConcurrentHashMap<K, V, S> {
+        ConcurrentHashMap::with_capacity_and_hasher(DEFAULT_CAPACITY, hash_builder)
+    }
+
+    /// Create hash table with specified capacity, which will be increase if needed
+    /// to next highest power of two, and hash builder to hash keys
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> ConcurrentHashMap<K, V, S> {
+        ConcurrentHashMap::with_capacity_load_factor_and_hasher(capacity, DEFAULT_LOAD_FACTOR, hash_builder)
+    }
+
+    /// Create hash table with specified capacity, load factor and hash builder
+    ///
+    /// # Panics
+    ///
+    /// Panics if load factor is not a positive number
+    pub fn with_capacity_load_factor_and_hasher(capacity: usize, load_factor: f32, hash_builder: S) -> ConcurrentHashMap<K, V, S> {
+        assert!(load_factor > 0.0, "load factor should be positive number but was {}", load_factor);
+        let capacity = round_up_to_next_highest_power_of_two(cmp::min(capacity, MAX_CAPACITY));
+        let table = Box::into_raw(Box::new(Table::new(capacity, load_factor)));
         ConcurrentHashMap {
-            table: table,
-            size: AtomicUsize::new(0)
+            table: AtomicPtr::new(table),
+            size: AtomicUsize::new(0),
+            load_factor: load_factor,
+            hash_builder: hash_builder
         }
     }
 
+    /// Return reference to hash builder of the table
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Return load factor of the table
+    pub fn load_factor(&self) -> f32 {
+        self.load_factor
+    }
+
     /// Check if table is empty
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -133,71 +301,1000 @@ impl ConcurrentHashMap {
 
     /// Return capacity of locks-buckets vector
     pub fn capacity(&self) -> usize {
-        self.table.capacity()
+        let _pinned = epoch::pin();
+        self.current_table().len()
     }
 
-    /// Insert key value pair into table 
+    /// Insert key value pair into table
     /// or update value if specified key is already in table
-    pub fn insert(&mut self, key: i32, val: i32) {
-        let index = self.capacity() & key as usize;
-        let mut guard = self.table[index].write().unwrap();
-        if put(key, val, &mut guard) {
-            self.size.fetch_add(1, Ordering::Relaxed);
+    /// Return previous value of the key if there was one
+    pub fn insert(&self, key: K, val: V) -> Option<V> {
+        let hash = self.hash(&key);
+        let _pinned = epoch::pin();
+        let result = {
+            let mut guard = self.write_bin(hash);
+            put(hash, key, val, &mut guard.link)
+        };
+        if result.is_none() {
+            self.increase_size();
         }
+        result
     }
 
     /// Remove specified key from table return value
     /// or None if key wasn't in the table
-    pub fn remove(&mut self, key: i32) -> Option<i32> {
-        let index = self.capacity() & key as usize;
-        let mut guard = self.table[index].write().unwrap();
-        let result = take(key, &mut guard);
-        println!("{:?}", result);
+    pub fn remove<Q: ?Sized>(&self, key: &Q) -> Option<V>
+            where K: Borrow<Q>, Q: Hash + Eq {
+        let hash = self.hash(key);
+        let _pinned = epoch::pin();
+        let mut guard = self.write_bin(hash);
+        let result = take(hash, key, &mut guard.link);
         if result.is_some() {
             self.size.fetch_sub(1, Ordering::Relaxed);
         }
         result
     }
+
+    /// Return copy of value of specified key
+    /// or None if key wasn't in the table
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<V>
+            where K: Borrow<Q>, Q: Hash + Eq, V: Clone {
+        let hash = self.hash(key);
+        let _pinned = epoch::pin();
+        let guard = self.read_bin(hash);
+        find(hash, key, &guard.link).map(|bucket| bucket.value.clone())
+    }
+
+    /// Return guard which dereferences to value of specified key
+    /// or None if key wasn't in the table
+    /// The bucket of the key is read locked until guard is dropped
+    pub fn get_ref<Q: ?Sized>(&self, key: &Q) -> Option<ReadGuard<K, V>>
+            where K: Borrow<Q>, Q: Hash + Eq {
+        let hash = self.hash(key);
+        let pinned = epoch::pin();
+        let guard = self.read_bin(hash);
+        let value = match find(hash, key, &guard.link) {
+            Some(bucket) => &bucket.value as *const V,
+            None => return None,
+        };
+        Some(ReadGuard::new(guard, value, pinned))
+    }
+
+    /// Check if table contains specified key
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+            where K: Borrow<Q>, Q: Hash + Eq {
+        let hash = self.hash(key);
+        let _pinned = epoch::pin();
+        let guard = self.read_bin(hash);
+        find(hash, key, &guard.link).is_some()
+    }
+
+    /// Return copy of value of specified key, if key wasn't in the table
+    /// insert value computed by function. The function is called at most once
+    /// while bucket of the key is locked
+    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> V
+            where F: FnOnce() -> V, V: Clone {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let hash = self.hash(&key);
+        let _pinned = epoch::pin();
+        let result = {
+            let mut guard = self.write_bin(hash);
+            if let Some(bucket) = find(hash, &key, &guard.link) {
+                return bucket.value.clone();
+            }
+            let value = f();
+            let result = value.clone();
+            put(hash, key, value, &mut guard.link);
+            result
+        };
+        self.increase_size();
+        result
+    }
+
+    /// Atomically compute new value for specified key from its current value
+    /// or None if key isn't in the table. If function returns None the key is removed
+    /// Return copy of new value
+    pub fn compute<F>(&self, key: K, f: F) -> Option<V>
+            where F: FnOnce(&K, Option<&V>) -> Option<V>, V: Clone {
+        let hash = self.hash(&key);
+        let _pinned = epoch::pin();
+        let (result, added, removed) = {
+            let mut guard = self.write_bin(hash);
+            let computed = {
+                let current = find(hash, &key, &guard.link).map(|bucket| &bucket.value);
+                f(&key, current)
+            };
+            match computed {
+                Some(value) => {
+                    let result = value.clone();
+                    let added = put(hash, key, value, &mut guard.link).is_none();
+                    (Some(result), added, false)
+                },
+                None => {
+                    let removed = take(hash, &key, &mut guard.link).is_some();
+                    (None, false, removed)
+                },
+            }
+        };
+        if added {
+            self.increase_size();
+        }
+        if removed {
+            self.size.fetch_sub(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Atomically insert specified value if key isn't in the table
+    /// otherwise replace current value with result of function
+    /// applied to current and specified values
+    /// Return copy of new value
+    pub fn merge<F>(&self, key: K, value: V, f: F) -> V
+            where F: FnOnce(&V, V) -> V, V: Clone {
+        let hash = self.hash(&key);
+        let _pinned = epoch::pin();
+        let (result, added) = {
+            let mut guard = self.write_bin(hash);
+            let value = match find(hash, &key, &guard.link) {
+                Some(bucket) => f(&bucket.value, value),
+                None => value,
+            };
+            let result = value.clone();
+            let added = put(hash, key, value, &mut guard.link).is_none();
+            (result, added)
+        };
+        if added {
+            self.increase_size();
+        }
+        result
+    }
+
+    /// Return entry of specified key for in-place manipulation
+    ///
+    /// The bucket of the key is write locked while entry or guard returned from it
+    /// is alive, so calling other methods of the map for keys from the same bucket
+    /// in the same thread would deadlock
+    pub fn entry(&self, key: K) -> Entry<K, V, S> {
+        let hash = self.hash(&key);
+        let pinned = epoch::pin();
+        let mut guard = self.write_bin(hash);
+        let bucket = find_mut(hash, &key, &mut guard.link).map(|bucket| bucket as *mut Bucket<K, V>);
+        match bucket {
+            Some(bucket) => Entry::Occupied(OccupiedEntry::new(self, guard, pinned, hash, key, bucket)),
+            None => Entry::Vacant(VacantEntry::new(self, guard, pinned, hash, key)),
+        }
+    }
+
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> usize {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        spread(hasher.finish())
+    }
 }
 
-fn put(key: i32, val: i32, guard: &mut RwLockWriteGuard<Link>) -> bool {
-    let contains = contains(key, guard);
-    if contains {
-        let mut link = iterate(key, guard);
-        link.value = Some(val);
+impl <K, V, S> ConcurrentHashMap<K, V, S> {
+
+    /// Apply function to each key value pair of the table
+    ///
+    /// If the table has at least parallelism threshold entries its buckets are split
+    /// between several threads, otherwise function is applied in current thread.
+    /// Function is called while bucket of the pair is read locked
+    pub fn for_each<F>(&self, parallelism_threshold: usize, f: F)
+            where F: Fn(&K, &V) + Sync, K: Send + Sync, V: Send + Sync {
+        self.parallel(parallelism_threshold,
+            |mut traverser| {
+                while let Some(guard) = traverser.next_read() {
+                    let mut current = guard.link.as_ref();
+                    while let Some(bucket) = current {
+                        f(&bucket.key, &bucket.value);
+                        current = bucket.next.as_ref();
+                    }
+                }
+            }
+        );
+    }
+
+    /// Transform each key value pair with map function and accumulate results
+    /// with reduce function, return None if the table is empty
+    ///
+    /// Parallelism threshold has the same meaning as in `for_each`
+    pub fn reduce<T, M, R>(&self, parallelism_threshold: usize, map: M, reduce: R) -> Option<T>
+            where M: Fn(&K, &V) -> T + Sync, R: Fn(T, T) -> T + Sync, T: Send,
+                K: Send + Sync, V: Send + Sync {
+        let results = self.parallel(parallelism_threshold,
+            |mut traverser| {
+                let mut result = None;
+                while let Some(guard) = traverser.next_read() {
+                    let mut current = guard.link.as_ref();
+                    while let Some(bucket) = current {
+                        let mapped = map(&bucket.key, &bucket.value);
+                        result = Some(match result {
+                            Some(accumulated) => reduce(accumulated, mapped),
+                            None => mapped,
+                        });
+                        current = bucket.next.as_ref();
+                    }
+                }
+                result
+            }
+        );
+        results.into_iter().fold(None,
+            |result, part| match (result, part) {
+                (Some(result), Some(part)) => Some(reduce(result, part)),
+                (result, None) => result,
+                (None, part) => part,
+            }
+        )
+    }
+
+    /// Return the first non None result of search function applied to key value pairs
+    /// of the table. When any thread finds result others stop searching
+    ///
+    /// Parallelism threshold has the same meaning as in `for_each`
+    pub fn search<T, F>(&self, parallelism_threshold: usize, f: F) -> Option<T>
+            where F: Fn(&K, &V) -> Option<T> + Sync, T: Send, K: Send + Sync, V: Send + Sync {
+        let found = AtomicBool::new(false);
+        let results = self.parallel(parallelism_threshold,
+            |mut traverser| {
+                while let Some(guard) = traverser.next_read() {
+                    if found.load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    let mut current = guard.link.as_ref();
+                    while let Some(bucket) = current {
+                        let result = f(&bucket.key, &bucket.value);
+                        if result.is_some() {
+                            found.store(true, Ordering::Relaxed);
+                            return result;
+                        }
+                        current = bucket.next.as_ref();
+                    }
+                }
+                None
+            }
+        );
+        results.into_iter().find(|result| result.is_some()).unwrap_or(None)
+    }
+
+    /// Retain only key value pairs for which predicate returns true,
+    /// the predicate could also modify the value
+    ///
+    /// Parallelism threshold has the same meaning as in `for_each`.
+    /// Predicate is called while bucket of the pair is write locked
+    pub fn retain<F>(&self, parallelism_threshold: usize, f: F)
+            where F: Fn(&K, &mut V) -> bool + Sync, K: Send + Sync, V: Send + Sync {
+        let size = &self.size;
+        self.parallel(parallelism_threshold,
+            |mut traverser| {
+                while let Some(mut guard) = traverser.next_write() {
+                    let removed = retain(&mut guard.link, &f);
+                    size.fetch_sub(removed, Ordering::Relaxed);
+                }
+            }
+        );
+    }
+
+    /// Return iterator over copies of key value pairs of the table
+    ///
+    /// Iteration is weakly consistent: buckets are read locked one at a time
+    /// and copied before they are yielded, so the iterator never blocks writers
+    /// for longer than copying of one bucket and never deadlocks with them.
+    /// Every entry that stays in the table during the whole iteration is yielded exactly once,
+    /// entries which are inserted or removed concurrently may or may not be yielded
+    pub fn iter(&self) -> Iter<K, V> where K: Clone, V: Clone {
+        let pinned = epoch::pin();
+        Iter::new(self.current_table(), pinned)
+    }
+
+    /// Return iterator over copies of keys of the table
+    /// with the same consistency as `iter`
+    pub fn keys(&self) -> Keys<K, V> where K: Clone {
+        let pinned = epoch::pin();
+        Keys::new(self.current_table(), pinned)
+    }
+
+    /// Return iterator over copies of values of the table
+    /// with the same consistency as `iter`
+    pub fn values(&self) -> Values<K, V> where V: Clone {
+        let pinned = epoch::pin();
+        Values::new(self.current_table(), pinned)
+    }
+
+    /// Return iterator which removes key value pairs from the table
+    ///
+    /// Buckets are write locked and emptied one at a time as the iterator advances,
+    /// so entries inserted into already drained buckets remain in the table.
+    /// Dropping the iterator drains the rest of buckets
+    pub fn drain(&self) -> Drain<K, V, S> {
+        Drain::new(self)
+    }
+
+    /// Return current table, the thread has to be pinned while it uses the table
+    fn current_table(&self) -> &Table<K, V> {
+        unsafe { &*self.table.load(Ordering::Acquire) }
+    }
+
+    /// Split bins of current table between worker threads and run task for each part,
+    /// the task runs in current thread if there are less entries than parallelism threshold
+    fn parallel<'a, T, F>(&'a self, parallelism_threshold: usize, task: F) -> Vec<T>
+            where F: Fn(Traverser<'a, K, V>) -> T + Sync, T: Send, K: Send + Sync, V: Send + Sync {
+        // workers use tables only while current thread is pinned
+        let _pinned = epoch::pin();
+        let table = self.current_table();
+        let capacity = table.len();
+        let workers = parallelism(self.size.load(Ordering::Relaxed), parallelism_threshold, capacity);
+        if workers == 1 {
+            return vec![task(Traverser::new(table, 0, capacity))];
+        }
+        let part = (capacity + workers - 1) / workers;
+        let task = &task;
+        crossbeam::scope(
+            |scope| {
+                let handles = (0..workers).map(
+                    |worker| {
+                        let start = worker * part;
+                        let end = cmp::min(start + part, capacity);
+                        scope.spawn(move || task(Traverser::new(table, start, end)))
+                    }
+                ).collect::<Vec<_>>();
+                handles.into_iter().map(|handle| handle.join()).collect()
+            }
+        )
     }
-    else {
-        let mut new_bucket = Link::new(Bucket::new(key, val));
-        let link = **guard;
-        new_bucket.next = Some(link);
-        **guard = new_bucket;
+
+    /// Read lock bin for specified hash following moved bins into newer tables
+    fn read_bin(&self, hash: usize) -> RwLockReadGuard<Bin<K, V>> {
+        let mut table = self.current_table();
+        loop {
+            let guard = table.bin(hash).read().unwrap();
+            if !guard.moved {
+                return guard;
+            }
+            table = table.next_table();
+        }
+    }
+
+    /// Lock bin for specified hash following moved bins into newer tables
+    fn write_bin(&self, hash: usize) -> RwLockWriteGuard<Bin<K, V>> {
+        let mut table = self.current_table();
+        loop {
+            let guard = table.bin(hash).write().unwrap();
+            if !guard.moved {
+                return guard;
+            }
+            table = table.next_table();
+        }
+    }
+
+    /// Count inserted entry and resize table if it is overloaded
+    /// should not be called while any bin is locked by current thread
+    fn increase_size(&self) {
+        self.size.fetch_add(1, Ordering::Relaxed);
+        self.try_resize();
+    }
+
+    fn try_resize(&self) {
+        let pinned = epoch::pin();
+        let table = self.current_table();
+        if self.size.load(Ordering::Relaxed) > table.threshold {
+            self.transfer(table, &pinned);
+        }
+    }
+
+    /// Move bins of specified table into next one. Every thread that takes part
+    /// in the transfer claims stride of bins, the thread which moves the last bin
+    /// publishes the next table as current and retires the old one
+    fn transfer(&self, table: &Table<K, V>, pinned: &Guard) {
+        let next = table.next_table_or_create(self.load_factor);
+        let capacity = table.len();
+        loop {
+            let start = table.transfer_index.fetch_add(TRANSFER_STRIDE, Ordering::Relaxed);
+            if start >= capacity {
+                break;
+            }
+            let end = cmp::min(start + TRANSFER_STRIDE, capacity);
+            for index in start..end {
+                table.move_bin(index, next);
+            }
+            let moved = end - start;
+            if table.transferred.fetch_add(moved, Ordering::AcqRel) + moved == capacity {
+                let current = table as *const Table<K, V> as *mut Table<K, V>;
+                let next = next as *const Table<K, V> as *mut Table<K, V>;
+                if self.table.compare_and_swap(current, next, Ordering::Release) == current {
+                    unsafe { pinned.defer_destroy(Box::into_raw(Box::new(MovedTable::new(current)))); }
+                }
+            }
+        }
+    }
+}
+
+impl <K, V, S> Drop for ConcurrentHashMap<K, V, S> {
+
+    fn drop(&mut self) {
+        let mut table = self.table.load(Ordering::Relaxed);
+        while !table.is_null() {
+            let next = unsafe { (*table).next.load(Ordering::Relaxed) };
+            unsafe { drop(Box::from_raw(table)); }
+            table = next;
+        }
     }
-    !contains
 }
 
-fn contains(key: i32, guard: &RwLockWriteGuard<Link>) -> bool {
-    (*iterate(key, guard)).key == Some(key)
+/// RAII guard which dereferences to value in the table
+/// and keeps its bucket read locked until dropped
+pub struct ReadGuard<'a, K: 'a, V: 'a> {
+    _guard: RwLockReadGuard<'a, Bin<K, V>>,
+    value: *const V,
+    _pinned: Guard
 }
 
-fn take(key: i32, guard: &mut RwLockWriteGuard<Link>) -> Option<i32> {
-    let contains = contains(key, guard);
-    if contains {
-        let mut link = iterate(key, guard);
-        match (*link).next {
-            Some(next) => link.next = next.next,
-            None => link.ptr = ptr::null_mut(),
+impl <'a, K, V> ReadGuard<'a, K, V> {
+
+    fn new(guard: RwLockReadGuard<'a, Bin<K, V>>, value: *const V, pinned: Guard) -> ReadGuard<'a, K, V> {
+        ReadGuard {
+            _guard: guard,
+            value: value,
+            _pinned: pinned
         }
-        (*link).value
     }
-    else {
+}
+
+impl <'a, K, V> Deref for ReadGuard<'a, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        unsafe { &*self.value }
+    }
+}
+
+impl <'a, K, V: Debug> Debug for ReadGuard<'a, K, V> {
+
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        write!(fmt, "[Read Guard {:?}]", **self)
+    }
+}
+
+impl <K: Hash + Eq, V, S: BuildHasher> ConcurrentMap<K, V> for ConcurrentHashMap<K, V, S> {
+
+    fn len(&self) -> usize {
+        ConcurrentHashMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ConcurrentHashMap::is_empty(self)
+    }
+
+    fn insert(&self, key: K, val: V) -> Option<V> {
+        ConcurrentHashMap::insert(self, key, val)
+    }
+
+    fn remove<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq {
+        ConcurrentHashMap::remove(self, key)
+    }
+
+    fn get<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq, V: Clone {
+        ConcurrentHashMap::get(self, key)
+    }
+
+    fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q>, Q: Hash + Eq {
+        ConcurrentHashMap::contains_key(self, key)
+    }
+}
+
+/// Visits all bins of the table which are not moved yet. Instead of moved bin
+/// it visits two bins of next table among which buckets of the moved bin were split
+struct Traverser<'a, K: 'a, V: 'a> {
+    table: &'a Table<K, V>,
+    index: usize,
+    end: usize,
+    pending: Vec<(&'a Table<K, V>, usize)>
+}
+
+impl <'a, K, V> Traverser<'a, K, V> {
+
+    fn new(table: &'a Table<K, V>, start: usize, end: usize) -> Traverser<'a, K, V> {
+        Traverser {
+            table: table,
+            index: start,
+            end: end,
+            pending: Vec::new()
+        }
+    }
+
+    fn next_bin(&mut self) -> Option<(&'a Table<K, V>, usize)> {
+        if let Some(bin) = self.pending.pop() {
+            return Some(bin);
+        }
+        if self.index < self.end {
+            self.index += 1;
+            return Some((self.table, self.index - 1));
+        }
+        None
+    }
+
+    fn split(&mut self, table: &'a Table<K, V>, index: usize) {
+        let next = table.next_table();
+        self.pending.push((next, index + table.len()));
+        self.pending.push((next, index));
+    }
+
+    /// Read lock next bin which is not moved
+    fn next_read(&mut self) -> Option<RwLockReadGuard<'a, Bin<K, V>>> {
+        while let Some((table, index)) = self.next_bin() {
+            let guard = table.bins[index].read().unwrap();
+            if !guard.moved {
+                return Some(guard);
+            }
+            self.split(table, index);
+        }
         None
     }
+
+    /// Write lock next bin which is not moved
+    fn next_write(&mut self) -> Option<RwLockWriteGuard<'a, Bin<K, V>>> {
+        while let Some((table, index)) = self.next_bin() {
+            let guard = table.bins[index].write().unwrap();
+            if !guard.moved {
+                return Some(guard);
+            }
+            self.split(table, index);
+        }
+        None
+    }
+}
+
+/// Copies buckets of visited bins with specified function
+/// and yields the copies one by one
+struct Snapshot<'a, K: 'a, V: 'a, T> {
+    traverser: Traverser<'a, K, V>,
+    buffer: Vec<T>,
+    copy: fn(&Bucket<K, V>) -> T,
+    _pinned: Guard
+}
+
+impl <'a, K, V, T> Snapshot<'a, K, V, T> {
+
+    fn new(table: &'a Table<K, V>, copy: fn(&Bucket<K, V>) -> T, pinned: Guard) -> Snapshot<'a, K, V, T> {
+        Snapshot {
+            traverser: Traverser::new(table, 0, table.len()),
+            buffer: Vec::new(),
+            copy: copy,
+            _pinned: pinned
+        }
+    }
+}
+
+impl <'a, K, V, T> Iterator for Snapshot<'a, K, V, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.buffer.is_empty() {
+            let guard = match self.traverser.next_read() {
+                Some(guard) => guard,
+                None => return None,
+            };
+            let mut current = guard.link.as_ref();
+            while let Some(bucket) = current {
+                self.buffer.push((self.copy)(bucket));
+                current = bucket.next.as_ref();
+            }
+        }
+        self.buffer.pop()
+    }
+}
+
+/// Iterator over copies of key value pairs of `ConcurrentHashMap`
+pub struct Iter<'a, K: 'a, V: 'a> {
+    inner: Snapshot<'a, K, V, (K, V)>
+}
+
+impl <'a, K: Clone, V: Clone> Iter<'a, K, V> {
+
+    fn new(table: &'a Table<K, V>, pinned: Guard) -> Iter<'a, K, V> {
+        Iter {
+            inner: Snapshot::new(table, copy_entry, pinned)
+        }
+    }
+}
+
+impl <'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next()
+    }
 }
 
-fn iterate(key: i32, guard: &RwLockWriteGuard<Link>) -> Link {
-    let mut link = **guard;
-    while (*link).key != Some(key) && (*link).next.is_some() {
-        link = (*link).next.unwrap();
+/// Iterator over copies of keys of `ConcurrentHashMap`
+pub struct Keys<'a, K: 'a, V: 'a> {
+    inner: Snapshot<'a, K, V, K>
+}
+
+impl <'a, K: Clone, V> Keys<'a, K, V> {
+
+    fn new(table: &'a Table<K, V>, pinned: Guard) -> Keys<'a, K, V> {
+        Keys {
+            inner: Snapshot::new(table, copy_key, pinned)
+        }
+    }
+}
+
+impl <'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.inner.next()
+    }
+}
+
+/// Iterator over copies of values of `ConcurrentHashMap`
+pub struct Values<'a, K: 'a, V: 'a> {
+    inner: Snapshot<'a, K, V, V>
+}
+
+impl <'a, K, V: Clone> Values<'a, K, V> {
+
+    fn new(table: &'a Table<K, V>, pinned: Guard) -> Values<'a, K, V> {
+        Values {
+            inner: Snapshot::new(table, copy_value, pinned)
+        }
+    }
+}
+
+impl <'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        self.inner.next()
+    }
+}
+
+fn copy_entry<K: Clone, V: Clone>(bucket: &Bucket<K, V>) -> (K, V) {
+    (bucket.key.clone(), bucket.value.clone())
+}
+
+fn copy_key<K: Clone, V>(bucket: &Bucket<K, V>) -> K {
+    bucket.key.clone()
+}
+
+fn copy_value<K, V: Clone>(bucket: &Bucket<K, V>) -> V {
+    bucket.value.clone()
+}
+
+/// Iterator which removes key value pairs from `ConcurrentHashMap`
+pub struct Drain<'a, K: 'a, V: 'a, S: 'a> {
+    map: &'a ConcurrentHashMap<K, V, S>,
+    traverser: Traverser<'a, K, V>,
+    buffer: Vec<(K, V)>,
+    _pinned: Guard
+}
+
+impl <'a, K, V, S> Drain<'a, K, V, S> {
+
+    fn new(map: &'a ConcurrentHashMap<K, V, S>) -> Drain<'a, K, V, S> {
+        let pinned = epoch::pin();
+        let table = map.current_table();
+        Drain {
+            map: map,
+            traverser: Traverser::new(table, 0, table.len()),
+            buffer: Vec::new(),
+            _pinned: pinned
+        }
+    }
+}
+
+impl <'a, K, V, S> Iterator for Drain<'a, K, V, S> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        while self.buffer.is_empty() {
+            let mut guard = match self.traverser.next_write() {
+                Some(guard) => guard,
+                None => return None,
+            };
+            let mut link = guard.link.take();
+            while let Some(bucket) = link {
+                let bucket = *bucket;
+                link = bucket.next;
+                self.buffer.push((bucket.key, bucket.value));
+            }
+            self.map.size.fetch_sub(self.buffer.len(), Ordering::Relaxed);
+        }
+        self.buffer.pop()
+    }
+}
+
+impl <'a, K, V, S> Drop for Drain<'a, K, V, S> {
+
+    fn drop(&mut self) {
+        for _ in self { }
+    }
+}
+
+/// A view into a single entry of the map which may be either occupied or vacant
+/// The bucket of the entry is write locked until the entry is dropped
+pub enum Entry<'a, K: 'a, V: 'a, S: 'a> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>)
+}
+
+impl <'a, K, V, S> Entry<'a, K, V, S> {
+
+    /// Return guard of value of the entry
+    /// insert specified value if the entry is vacant
+    pub fn or_insert(self, default: V) -> WriteGuard<'a, K, V, S> {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Return guard of value of the entry
+    /// insert result of the function if the entry is vacant
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> WriteGuard<'a, K, V, S> {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Modify value of occupied entry with specified function
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Entry<'a, K, V, S> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            },
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+
+    /// Return key of the entry
+    pub fn key(&self) -> &K {
+        match *self {
+            Entry::Occupied(ref entry) => entry.key(),
+            Entry::Vacant(ref entry) => entry.key(),
+        }
     }
-    link
+}
+
+/// A view into an occupied entry of the map
+pub struct OccupiedEntry<'a, K: 'a, V: 'a, S: 'a> {
+    map: &'a ConcurrentHashMap<K, V, S>,
+    guard: RwLockWriteGuard<'a, Bin<K, V>>,
+    hash: usize,
+    key: K,
+    bucket: *mut Bucket<K, V>,
+    pinned: Guard
+}
+
+impl <'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+
+    fn new(map: &'a ConcurrentHashMap<K, V, S>, guard: RwLockWriteGuard<'a, Bin<K, V>>, pinned: Guard,
+            hash: usize, key: K, bucket: *mut Bucket<K, V>) -> OccupiedEntry<'a, K, V, S> {
+        OccupiedEntry {
+            map: map,
+            guard: guard,
+            hash: hash,
+            key: key,
+            bucket: bucket,
+            pinned: pinned
+        }
+    }
+
+    /// Return key of the entry
+    pub fn key(&self) -> &K {
+        unsafe { &(*self.bucket).key }
+    }
+
+    /// Return value of the entry
+    pub fn get(&self) -> &V {
+        unsafe { &(*self.bucket).value }
+    }
+
+    /// Return mutable value of the entry
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe { &mut (*self.bucket).value }
+    }
+
+    /// Replace value of the entry and return the old one
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Convert entry into guard of its value
+    pub fn into_mut(self) -> WriteGuard<'a, K, V, S> {
+        let value = unsafe { &mut (*self.bucket).value as *mut V };
+        WriteGuard::new(self.map, self.guard, self.pinned, value, false)
+    }
+}
+
+impl <'a, K: Eq, V, S> OccupiedEntry<'a, K, V, S> {
+
+    /// Remove entry from the map and return its value
+    pub fn remove(mut self) -> V {
+        let value = take(self.hash, &self.key, &mut self.guard.link).unwrap();
+        self.map.size.fetch_sub(1, Ordering::Relaxed);
+        value
+    }
+}
+
+/// A view into a vacant entry of the map
+pub struct VacantEntry<'a, K: 'a, V: 'a, S: 'a> {
+    map: &'a ConcurrentHashMap<K, V, S>,
+    guard: RwLockWriteGuard<'a, Bin<K, V>>,
+    hash: usize,
+    key: K,
+    pinned: Guard
+}
+
+impl <'a, K, V, S> VacantEntry<'a, K, V, S> {
+
+    fn new(map: &'a ConcurrentHashMap<K, V, S>, guard: RwLockWriteGuard<'a, Bin<K, V>>, pinned: Guard,
+            hash: usize, key: K) -> VacantEntry<'a, K, V, S> {
+        VacantEntry {
+            map: map,
+            guard: guard,
+            hash: hash,
+            key: key,
+            pinned: pinned
+        }
+    }
+
+    /// Return key of the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Take ownership of the key
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Insert value into the map and return its guard
+    pub fn insert(mut self, value: V) -> WriteGuard<'a, K, V, S> {
+        let next = self.guard.link.take();
+        self.guard.link = Some(Box::new(Bucket::new(self.hash, self.key, value, next)));
+        self.map.size.fetch_add(1, Ordering::Relaxed);
+        let value = &mut self.guard.link.as_mut().unwrap().value as *mut V;
+        WriteGuard::new(self.map, self.guard, self.pinned, value, true)
+    }
+}
+
+/// RAII guard which dereferences to mutable value in the table
+/// and keeps its bucket write locked until dropped
+pub struct WriteGuard<'a, K: 'a, V: 'a, S: 'a> {
+    map: &'a ConcurrentHashMap<K, V, S>,
+    guard: Option<RwLockWriteGuard<'a, Bin<K, V>>>,
+    value: *mut V,
+    inserted: bool,
+    _pinned: Guard
+}
+
+impl <'a, K, V, S> WriteGuard<'a, K, V, S> {
+
+    fn new(map: &'a ConcurrentHashMap<K, V, S>, guard: RwLockWriteGuard<'a, Bin<K, V>>, pinned: Guard,
+            value: *mut V, inserted: bool) -> WriteGuard<'a, K, V, S> {
+        WriteGuard {
+            map: map,
+            guard: Some(guard),
+            value: value,
+            inserted: inserted,
+            _pinned: pinned
+        }
+    }
+}
+
+impl <'a, K, V, S> Deref for WriteGuard<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        unsafe { &*self.value }
+    }
+}
+
+impl <'a, K, V, S> DerefMut for WriteGuard<'a, K, V, S> {
+
+    fn deref_mut(&mut self) -> &mut V {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl <'a, K, V, S> Drop for WriteGuard<'a, K, V, S> {
+
+    fn drop(&mut self) {
+        // table could be resized only when bucket is unlocked
+        drop(self.guard.take());
+        if self.inserted {
+            self.map.try_resize();
+        }
+    }
+}
+
+impl <'a, K, V: Debug, S> Debug for WriteGuard<'a, K, V, S> {
+
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        write!(fmt, "[Write Guard {:?}]", **self)
+    }
+}
+
+/// Number of worker threads for bulk operation
+fn parallelism(size: usize, parallelism_threshold: usize, capacity: usize) -> usize {
+    let workers = size / cmp::max(parallelism_threshold, 1);
+    cmp::max(cmp::min(cmp::min(workers, num_cpus::get()), capacity), 1)
+}
+
+fn put<K: Eq, V>(hash: usize, key: K, val: V, link: &mut Link<K, V>) -> Option<V> {
+    {
+        let mut current = link.as_mut();
+        while let Some(bucket) = current {
+            if bucket.hash == hash && bucket.key == key {
+                return Some(mem::replace(&mut bucket.value, val));
+            }
+            current = bucket.next.as_mut();
+        }
+    }
+    let next = link.take();
+    *link = Some(Box::new(Bucket::new(hash, key, val, next)));
+    None
+}
+
+/// Remove buckets for which predicate returns false, return number of removed buckets
+fn retain<K, V, F>(link: &mut Link<K, V>, f: &F) -> usize
+        where F: Fn(&K, &mut V) -> bool {
+    let mut removed = 0;
+    let mut rest = link.take();
+    let mut tail = link;
+    while let Some(mut bucket) = rest {
+        rest = bucket.next.take();
+        if f(&bucket.key, &mut bucket.value) {
+            *tail = Some(bucket);
+            tail = &mut tail.as_mut().unwrap().next;
+        }
+        else {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+fn find<'a, K, V, Q: ?Sized>(hash: usize, key: &Q, link: &'a Link<K, V>) -> Option<&'a Bucket<K, V>>
+        where K: Borrow<Q>, Q: Eq {
+    let mut current = link.as_ref();
+    while let Some(bucket) = current {
+        if bucket.hash == hash && bucket.key.borrow() == key {
+            return Some(&**bucket);
+        }
+        current = bucket.next.as_ref();
+    }
+    None
+}
+
+fn find_mut<'a, K, V, Q: ?Sized>(hash: usize, key: &Q, link: &'a mut Link<K, V>) -> Option<&'a mut Bucket<K, V>>
+        where K: Borrow<Q>, Q: Eq {
+    let mut current = link.as_mut();
+    while let Some(bucket) = current {
+        if bucket.hash == hash && bucket.key.borrow() == key {
+            return Some(&mut **bucket);
+        }
+        current = bucket.next.as_mut();
+    }
+    None
+}
+
+fn take<K, V, Q: ?Sized>(hash: usize, key: &Q, link: &mut Link<K, V>) -> Option<V>
+        where K: Borrow<Q>, Q: Eq {
+    let mut current = link;
+    while current.as_ref().map_or(false, |bucket| bucket.hash != hash || bucket.key.borrow() != key) {
+        current = &mut current.as_mut().unwrap().next;
+    }
+    current.take().map(
+        |bucket| {
+            let bucket = *bucket;
+            *current = bucket.next;
+            bucket.value
+        }
+    )
 }
@@ -0,0 +1,474 @@
+use std::ptr;
+use std::mem;
+use std::cmp;
+
+use std::borrow::Borrow;
+
+use std::marker::PhantomData;
+
+use std::hash::{Hash, Hasher, BuildHasher};
+use std::collections::hash_map::RandomState;
+
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use super::{ConcurrentMap, spread};
+use super::super::round_up_to_next_highest_power_of_two;
+use super::super::reclaim::{Reclaim, Protect, Epoch};
+
+const BITS: usize = mem::size_of::<usize>() * 8;
+const HIGH_BIT: usize = 1 << (BITS - 1);
+const MARK: usize = 1;
+
+const DEFAULT_CAPACITY: usize = 16;
+const MAX_CAPACITY: usize = 1 << (BITS - 2);
+const LOAD_FACTOR: usize = 2;
+
+/// Hazard slots used during traversal of the list
+const NEXT: usize = 0;
+const CURRENT: usize = 1;
+const PREVIOUS: usize = 2;
+const VALUE: usize = 3;
+
+/// Node of split ordered list. Buckets point to dummy nodes, which have no key and value,
+/// regular nodes have key and value which is null when the entry is removed.
+/// Lowest bit of next pointer marks node as removed from the list
+struct Node<K, V> {
+    order: usize,
+    key: Option<K>,
+    value: AtomicPtr<V>,
+    next: AtomicUsize
+}
+
+impl <K, V> Node<K, V> {
+
+    fn dummy(order: usize) -> Node<K, V> {
+        Node {
+            order: order,
+            key: None,
+            value: AtomicPtr::new(ptr::null_mut()),
+            next: AtomicUsize::new(0)
+        }
+    }
+
+    fn regular(order: usize, key: K, value: V) -> Node<K, V> {
+        Node {
+            order: order,
+            key: Some(key),
+            value: AtomicPtr::new(Box::into_raw(Box::new(value))),
+            next: AtomicUsize::new(0)
+        }
+    }
+
+    fn mark(&self) {
+        self.next.fetch_or(MARK, Ordering::AcqRel);
+    }
+}
+
+fn is_marked(link: usize) -> bool {
+    link & MARK == MARK
+}
+
+fn unmarked<K, V>(link: usize) -> *mut Node<K, V> {
+    (link & !MARK) as *mut Node<K, V>
+}
+
+/// Segment of buckets, segment with index `k` holds buckets from `2^(k-1)` to `2^k`
+struct Segment<K, V> {
+    buckets: Vec<AtomicPtr<Node<K, V>>>
+}
+
+impl <K, V> Segment<K, V> {
+
+    fn new(size: usize) -> Segment<K, V> {
+        let mut buckets = Vec::with_capacity(size);
+        for _ in 0..size {
+            buckets.push(AtomicPtr::new(ptr::null_mut()));
+        }
+        Segment {
+            buckets: buckets
+        }
+    }
+}
+
+/// A lock-free hash table based on split-ordered lists of Shalev and Shavit
+///
+/// All entries are kept in one lock-free linked list sorted by bit reversed hashes,
+/// buckets are shortcuts to dummy nodes in the list. When the table grows buckets are not moved,
+/// new buckets are lazily initialized by inserting dummy nodes which split chains of parent buckets.
+/// Insertion and removal use compare-and-swap on list links and value pointers
+///
+/// Values are shared with concurrent readers, so operations return copies of values.
+/// Removed nodes and replaced values are destroyed once no thread could read them,
+/// by default with epoch based reclamation. Maps used by threads which could be parked
+/// in the middle of operations could be created with `Hazards` reclamation instead.
+/// Nodes could be destroyed by another thread, so keys and values have to be `Send + 'static`
+pub struct LockFreeHashMap<K, V, S = RandomState, R = Epoch> {
+    segments: Vec<AtomicPtr<Segment<K, V>>>,
+    buckets: AtomicUsize,
+    size: AtomicUsize,
+    hash_builder: S,
+    reclaim: PhantomData<R>
+}
+
+unsafe impl <K: Send, V: Send, S: Send, R> Send for LockFreeHashMap<K, V, S, R> { }
+unsafe impl <K: Send + Sync, V: Send + Sync, S: Sync, R> Sync for LockFreeHashMap<K, V, S, R> { }
+
+impl <K: Hash + Eq + Send + 'static, V: Send + 'static, S: BuildHasher + Default, R: Reclaim> Default for LockFreeHashMap<K, V, S, R> {
+
+    fn default() -> LockFreeHashMap<K, V, S, R> {
+        LockFreeHashMap::with_hasher(Default::default())
+    }
+}
+
+impl <K: Hash + Eq + Send + 'static, V: Send + 'static> LockFreeHashMap<K, V, RandomState, Epoch> {
+
+    /// Create hash table with default number of buckets which is 16
+    pub fn new() -> LockFreeHashMap<K, V, RandomState, Epoch> {
+        LockFreeHashMap::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create hash table with specified number of buckets which will be
+    /// increase if needed to next highest power of two
+    pub fn with_capacity(capacity: usize) -> LockFreeHashMap<K, V, RandomState, Epoch> {
+        LockFreeHashMap::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl <K: Hash + Eq + Send + 'static, V: Send + 'static, S: BuildHasher, R: Reclaim> LockFreeHashMap<K, V, S, R> {
+
+    /// Create hash table with default number of buckets which will use
+    /// specified hash builder to hash keys
+    pub fn with_hasher(hash_builder: S) -> LockFreeHashMap<K, V, S, R> {
+        LockFreeHashMap::with_capacity_and_hasher(DEFAULT_CAPACITY, hash_builder)
+    }
+
+    /// Create hash table with specified number of buckets, which will be increase if needed
+    /// to next highest power of two, and hash builder to hash keys
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> LockFreeHashMap<K, V, S, R> {
+        let capacity = round_up_to_next_highest_power_of_two(cmp::min(capacity, MAX_CAPACITY));
+        let mut segments = Vec::with_capacity(BITS);
+        for _ in 0..BITS {
+            segments.push(AtomicPtr::new(ptr::null_mut()));
+        }
+        let map = LockFreeHashMap {
+            segments: segments,
+            buckets: AtomicUsize::new(capacity),
+            size: AtomicUsize::new(0),
+            hash_builder: hash_builder,
+            reclaim: PhantomData
+        };
+        let head = Box::into_raw(Box::new(Node::dummy(dummy_order(0))));
+        map.slot(0).store(head, Ordering::Release);
+        map
+    }
+
+    /// Return reference to hash builder of the table
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Check if table is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return size of table
+    pub fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Return number of buckets
+    pub fn capacity(&self) -> usize {
+        self.buckets.load(Ordering::Relaxed)
+    }
+
+    /// Insert key value pair into table
+    /// or update value if specified key is already in table
+    /// Return copy of previous value of the key if there was one
+    pub fn insert(&self, key: K, val: V) -> Option<V> where V: Clone {
+        let hash = self.hash(&key);
+        let guard = R::guard();
+        let head = self.bucket(hash, &guard);
+        let node = Box::into_raw(Box::new(Node::regular(regular_order(hash), key, val)));
+        let (order, key, value) = unsafe { ((*node).order, (*node).key.as_ref().unwrap(), (*node).value.load(Ordering::Relaxed)) };
+        loop {
+            let (prev, current, found) = self.find(head, order, Some(key), &guard);
+            if found {
+                let existing = unsafe { &*current };
+                let old = existing.value.load(Ordering::Acquire);
+                if old.is_null() {
+                    // the node is being removed, help to mark it and retry
+                    existing.mark();
+                    continue;
+                }
+                if existing.value.compare_and_swap(old, value, Ordering::AcqRel) == old {
+                    unsafe { drop(Box::from_raw(node)); }
+                    let result = unsafe { (*old).clone() };
+                    unsafe { guard.retire(old); }
+                    return Some(result);
+                }
+            }
+            else {
+                unsafe { (*node).next.store(current as usize, Ordering::Relaxed); }
+                if prev.compare_and_swap(current as usize, node as usize, Ordering::AcqRel) == current as usize {
+                    self.increase_size();
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Remove specified key from table return copy of value
+    /// or None if key wasn't in the table
+    pub fn remove<Q: ?Sized>(&self, key: &Q) -> Option<V>
+            where K: Borrow<Q>, Q: Hash + Eq, V: Clone {
+        let hash = self.hash(key);
+        let guard = R::guard();
+        let head = self.bucket(hash, &guard);
+        let order = regular_order(hash);
+        loop {
+            let (_, current, found) = self.find(head, order, Some(key), &guard);
+            if !found {
+                return None;
+            }
+            let node = unsafe { &*current };
+            let old = node.value.load(Ordering::Acquire);
+            if old.is_null() {
+                return None;
+            }
+            if node.value.compare_and_swap(old, ptr::null_mut(), Ordering::AcqRel) == old {
+                node.mark();
+                // unlink marked node
+                self.find(head, order, Some(key), &guard);
+                self.size.fetch_sub(1, Ordering::Relaxed);
+                let result = unsafe { (*old).clone() };
+                unsafe { guard.retire(old); }
+                return Some(result);
+            }
+        }
+    }
+
+    /// Return copy of value of specified key
+    /// or None if key wasn't in the table
+    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<V>
+            where K: Borrow<Q>, Q: Hash + Eq, V: Clone {
+        let hash = self.hash(key);
+        let guard = R::guard();
+        let (_, current, found) = self.find(self.bucket(hash, &guard), regular_order(hash), Some(key), &guard);
+        if !found {
+            return None;
+        }
+        let value = guard.protect_ptr(VALUE, unsafe { &(*current).value });
+        if value.is_null() {
+            None
+        }
+        else {
+            Some(unsafe { (*value).clone() })
+        }
+    }
+
+    /// Check if table contains specified key
+    pub fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool
+            where K: Borrow<Q>, Q: Hash + Eq {
+        let hash = self.hash(key);
+        let guard = R::guard();
+        let (_, current, found) = self.find(self.bucket(hash, &guard), regular_order(hash), Some(key), &guard);
+        found && unsafe { !(*current).value.load(Ordering::Acquire).is_null() }
+    }
+
+    fn hash<Q: ?Sized + Hash>(&self, key: &Q) -> usize {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        spread(hasher.finish())
+    }
+
+    fn increase_size(&self) {
+        let size = self.size.fetch_add(1, Ordering::Relaxed) + 1;
+        let buckets = self.capacity();
+        if size > buckets * LOAD_FACTOR && buckets < MAX_CAPACITY {
+            self.buckets.compare_and_swap(buckets, buckets << 1, Ordering::Relaxed);
+        }
+    }
+
+    /// Return dummy node of the bucket of specified hash
+    fn bucket(&self, hash: usize, guard: &R::Guard) -> &Node<K, V> {
+        self.bucket_at(hash & (self.capacity() - 1), guard)
+    }
+
+    fn bucket_at(&self, index: usize, guard: &R::Guard) -> &Node<K, V> {
+        let dummy = self.slot(index).load(Ordering::Acquire);
+        if dummy.is_null() {
+            self.initialize_bucket(index, guard)
+        }
+        else {
+            unsafe { &*dummy }
+        }
+    }
+
+    /// Insert dummy node of the bucket into the list starting from its parent bucket,
+    /// which is the bucket index without the highest bit
+    fn initialize_bucket(&self, index: usize, guard: &R::Guard) -> &Node<K, V> {
+        let parent = self.bucket_at(index & !(HIGH_BIT >> index.leading_zeros()), guard);
+        let order = dummy_order(index);
+        let mut dummy = Box::into_raw(Box::new(Node::dummy(order)));
+        loop {
+            let (prev, current, found) = self.find::<K>(parent, order, None, guard);
+            if found {
+                unsafe { drop(Box::from_raw(dummy)); }
+                dummy = current;
+                break;
+            }
+            unsafe { (*dummy).next.store(current as usize, Ordering::Relaxed); }
+            if prev.compare_and_swap(current as usize, dummy as usize, Ordering::AcqRel) == current as usize {
+                break;
+            }
+        }
+        self.slot(index).compare_and_swap(ptr::null_mut(), dummy, Ordering::AcqRel);
+        unsafe { &*dummy }
+    }
+
+    /// Find position of node with specified order and key in the list starting from the head,
+    /// marked nodes on the way are unlinked. Return link to the found node or to the first node
+    /// which is greater, the node and whether it was found
+    fn find<'a, Q: ?Sized>(&'a self, head: &'a Node<K, V>, order: usize, key: Option<&Q>, guard: &R::Guard)
+            -> (&'a AtomicUsize, *mut Node<K, V>, bool)
+            where K: Borrow<Q>, Q: Eq {
+        'retry: loop {
+            let mut prev = &head.next;
+            let mut current = guard.protect(CURRENT, prev);
+            loop {
+                if is_marked(current) {
+                    continue 'retry;
+                }
+                let node = unmarked::<K, V>(current);
+                if node.is_null() {
+                    return (prev, node, false);
+                }
+                let next = guard.protect(NEXT, unsafe { &(*node).next });
+                // the node is still linked, so the next one was not removed before it was protected
+                if prev.load(Ordering::Acquire) != current {
+                    continue 'retry;
+                }
+                if is_marked(next) {
+                    if prev.compare_and_swap(current, next & !MARK, Ordering::AcqRel) != current {
+                        continue 'retry;
+                    }
+                    unsafe { guard.retire(node); }
+                    current = next & !MARK;
+                    guard.copy(CURRENT, current);
+                    continue;
+                }
+                let node = unsafe { &*node };
+                if node.order > order {
+                    return (prev, current as *mut Node<K, V>, false);
+                }
+                if node.order == order && node.key.as_ref().map(|key| key.borrow()) == key {
+                    return (prev, current as *mut Node<K, V>, true);
+                }
+                prev = &node.next;
+                guard.copy(PREVIOUS, current);
+                current = next;
+                guard.copy(CURRENT, current);
+            }
+        }
+    }
+}
+
+impl <K, V, S, R> LockFreeHashMap<K, V, S, R> {
+
+    /// Return pointer to dummy node of the bucket allocating segment if needed
+    fn slot(&self, index: usize) -> &AtomicPtr<Node<K, V>> {
+        let (segment, offset) = if index == 0 {
+            (0, 0)
+        }
+        else {
+            let segment = BITS - index.leading_zeros() as usize;
+            (segment, index - (1 << (segment - 1)))
+        };
+        let mut current = self.segments[segment].load(Ordering::Acquire);
+        if current.is_null() {
+            let size = if segment == 0 { 1 } else { 1 << (segment - 1) };
+            let created = Box::into_raw(Box::new(Segment::new(size)));
+            current = self.segments[segment].compare_and_swap(ptr::null_mut(), created, Ordering::AcqRel);
+            if current.is_null() {
+                current = created;
+            }
+            else {
+                unsafe { drop(Box::from_raw(created)); }
+            }
+        }
+        let segment = unsafe { &*current };
+        &segment.buckets[offset]
+    }
+}
+
+impl <K, V, S, R> Drop for LockFreeHashMap<K, V, S, R> {
+
+    fn drop(&mut self) {
+        unsafe {
+            let mut node = self.slot(0).load(Ordering::Relaxed);
+            while !node.is_null() {
+                let next = unmarked((*node).next.load(Ordering::Relaxed));
+                let value = (*node).value.load(Ordering::Relaxed);
+                if !value.is_null() {
+                    drop(Box::from_raw(value));
+                }
+                drop(Box::from_raw(node));
+                node = next;
+            }
+            for segment in &self.segments {
+                let segment = segment.load(Ordering::Relaxed);
+                if !segment.is_null() {
+                    drop(Box::from_raw(segment));
+                }
+            }
+        }
+    }
+}
+
+impl <K: Hash + Eq + Send + 'static, V: Clone + Send + 'static, S: BuildHasher, R: Reclaim> ConcurrentMap<K, V> for LockFreeHashMap<K, V, S, R> {
+
+    fn len(&self) -> usize {
+        LockFreeHashMap::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        LockFreeHashMap::is_empty(self)
+    }
+
+    fn insert(&self, key: K, val: V) -> Option<V> {
+        LockFreeHashMap::insert(self, key, val)
+    }
+
+    fn remove<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq {
+        LockFreeHashMap::remove(self, key)
+    }
+
+    fn get<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq {
+        LockFreeHashMap::get(self, key)
+    }
+
+    fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q>, Q: Hash + Eq {
+        LockFreeHashMap::contains_key(self, key)
+    }
+}
+
+/// Split order key of regular node is bit reversed hash with the highest bit set,
+/// so it is always odd and placed after dummy node of its bucket
+fn regular_order(hash: usize) -> usize {
+    reverse(hash | HIGH_BIT)
+}
+
+/// Split order key of dummy node is bit reversed bucket index which is always even
+fn dummy_order(index: usize) -> usize {
+    reverse(index & !HIGH_BIT)
+}
+
+fn reverse(mut value: usize) -> usize {
+    let mut reversed = 0;
+    for _ in 0..BITS {
+        reversed = (reversed << 1) | (value & 1);
+        value >>= 1;
+    }
+    reversed
+}
@@ -1,3 +1,31 @@
-pub use self::concurrent_hash_map::ConcurrentHashMap;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+pub use self::concurrent_hash_map::{ConcurrentHashMap, ReadGuard, WriteGuard};
+pub use self::concurrent_hash_map::{Entry, OccupiedEntry, VacantEntry};
+pub use self::concurrent_hash_map::{Iter, Keys, Values, Drain};
+pub use self::lock_free_hash_map::LockFreeHashMap;
 
 mod concurrent_hash_map;
+mod lock_free_hash_map;
+
+pub trait ConcurrentMap<K, V> {
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool;
+
+    fn insert(&self, key: K, val: V) -> Option<V>;
+
+    fn remove<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq;
+
+    fn get<Q: ?Sized>(&self, key: &Q) -> Option<V> where K: Borrow<Q>, Q: Hash + Eq, V: Clone;
+
+    fn contains_key<Q: ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q>, Q: Hash + Eq;
+}
+
+/// Mix higher bits of hash into lower ones which are used to find bucket index,
+/// thus hashers that vary only in high bits would not end up in one bucket
+fn spread(hash: u64) -> usize {
+    (hash ^ (hash >> 32) ^ (hash >> 16)) as usize
+}
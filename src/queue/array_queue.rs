@@ -2,12 +2,17 @@ extern crate alloc;
 
 use self::alloc::raw_vec::RawVec;
 use std::ptr;
+use std::cmp;
 use std::cmp::PartialEq;
 use std::option::Option;
 use std::sync::{Mutex, Condvar, Arc};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use super::BlockingQueue;
+use super::{BlockingQueue, Closed};
+use super::channel;
+use super::channel::{Sender, Receiver};
+use super::select::{Observers, Operation, Selectable, Signal};
 use super::super::round_up_to_next_highest_power_of_two;
 
 const MIN_CAPACITY: usize = 16;
@@ -21,8 +26,10 @@ struct ArrayBlockingQueueInner<T> {
     head: AtomicUsize,
     size: AtomicUsize,
     data: RawVec<T>,
+    closed: AtomicBool,
     empty: Condvar,
-    full: Condvar
+    full: Condvar,
+    observers: Observers
 }
 
 impl <T> ArrayBlockingQueueInner<T> {
@@ -39,8 +46,10 @@ impl <T> ArrayBlockingQueueInner<T> {
             head: AtomicUsize::new(0),
             size: AtomicUsize::new(0),
             data: RawVec::with_capacity(capacity),
+            closed: AtomicBool::new(false),
             empty: Condvar::new(),
-            full: Condvar::new()
+            full: Condvar::new(),
+            observers: Observers::new()
         }
     }
 
@@ -68,6 +77,41 @@ impl <T> ArrayBlockingQueueInner<T> {
         self.size() == 0
     }
 
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    fn close(&self) {
+        let guard = self.mutex.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.empty.notify_all();
+        self.full.notify_all();
+        self.observers.notify();
+        drop(guard);
+    }
+
+    fn is_ready(&self, op: Operation) -> bool {
+        let guard = self.mutex.lock().unwrap();
+        let ready = self.is_closed() || match op {
+            Operation::Send => !self.is_full(),
+            Operation::Recv => !self.is_empty(),
+        };
+        drop(guard);
+        ready
+    }
+
+    fn watch(&self, signal: &Arc<Signal>) {
+        let guard = self.mutex.lock().unwrap();
+        self.observers.add(signal);
+        drop(guard);
+    }
+
+    fn unwatch(&self, signal: &Arc<Signal>) {
+        let guard = self.mutex.lock().unwrap();
+        self.observers.remove(signal);
+        drop(guard);
+    }
+
     fn head(&self) -> usize {
         self.head.load(Ordering::Relaxed)
     }
@@ -93,18 +137,74 @@ impl <T> ArrayBlockingQueueInner<T> {
         len
     }
 
-    fn enqueue(&self, val: T) {
+    fn enqueue(&self, val: T) -> Result<(), T> {
         let mut guard = self.mutex.lock().unwrap();
-        while self.is_full() {
+        while self.is_full() && !self.is_closed() {
             guard = self.full.wait(guard).unwrap();
         }
+        if self.is_closed() {
+            return Err(val);
+        }
+        self.put(val);
+        drop(guard);
+        Ok(())
+    }
+
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        let start = Instant::now();
+        let mut guard = self.mutex.lock().unwrap();
+        while self.is_full() && !self.is_closed() {
+            let elapsed = start.elapsed();
+            if elapsed >= timeout {
+                return Err(val);
+            }
+            guard = self.full.wait_timeout(guard, timeout - elapsed).unwrap().0;
+        }
+        if self.is_closed() {
+            return Err(val);
+        }
+        self.put(val);
+        drop(guard);
+        Ok(())
+    }
+
+    fn enqueue_all<I: Iterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut iter = iter.peekable();
+        let mut guard = self.mutex.lock().unwrap();
+        while iter.peek().is_some() {
+            while self.is_full() && !self.is_closed() {
+                guard = self.full.wait(guard).unwrap();
+            }
+            if self.is_closed() {
+                return Err(iter.collect());
+            }
+            while !self.is_full() {
+                match iter.next() {
+                    Some(val) => self.write(val),
+                    None => break,
+                }
+            }
+            self.empty.notify_all();
+            self.observers.notify();
+        }
+        drop(guard);
+        Ok(())
+    }
+
+    /// Write value at tail of the queue and notify threads waiting for it
+    /// should be called under the lock when queue is not full
+    fn put(&self, val: T) {
+        self.write(val);
+        self.empty.notify_all();
+        self.observers.notify();
+    }
+
+    fn write(&self, val: T) {
         let index = self.next_free_index();
         unsafe {
             let tail = self.data.ptr().offset(index as isize);
             ptr::write(tail, val);
         }
-        self.empty.notify_all();
-        drop(guard);
     }
 
     fn next_free_index(&self) -> usize {
@@ -112,39 +212,100 @@ impl <T> ArrayBlockingQueueInner<T> {
         (self.head() + self.increase_size()) & mask
     }
 
-    fn dequeue(&self) -> T {
+    fn dequeue(&self) -> Result<T, Closed> {
         let mut guard = self.mutex.lock().unwrap();
-        while self.is_empty() {
+        while self.is_empty() && !self.is_closed() {
             guard = self.empty.wait(guard).unwrap();
         }
+        if self.is_empty() {
+            return Err(Closed);
+        }
+        let val = self.take();
+        drop(guard);
+        Ok(val)
+    }
+
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        let start = Instant::now();
+        let mut guard = self.mutex.lock().unwrap();
+        while self.is_empty() {
+            let elapsed = start.elapsed();
+            if elapsed >= timeout || self.is_closed() {
+                return None;
+            }
+            guard = self.empty.wait_timeout(guard, timeout - elapsed).unwrap().0;
+        }
+        let val = self.take();
+        drop(guard);
+        Some(val)
+    }
+
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let guard = self.mutex.lock().unwrap();
+        let count = cmp::min(self.size(), max);
+        target.reserve(count);
+        for _ in 0..count {
+            target.push(self.read());
+        }
+        if count > 0 {
+            self.full.notify_all();
+            self.observers.notify();
+        }
+        drop(guard);
+        count
+    }
+
+    /// Read value from head of the queue and notify threads waiting for free space
+    /// should be called under the lock when queue is not empty
+    fn take(&self) -> T {
+        let val = self.read();
+        self.full.notify_all();
+        self.observers.notify();
+        val
+    }
+
+    fn read(&self) -> T {
         let index = self.next_head();
         let val = unsafe {
             let head = self.data.ptr().offset(index as isize);
             ptr::read(head)
         };
         self.decrease_size();
-        self.full.notify_all();
-        drop(guard);
         val
     }
 
     fn offer(&self, val: T) -> bool {
-        if !self.is_full() {
-            self.enqueue(val);
-            true
-        } else {
-            false
+        self.try_enqueue(val).is_ok()
+    }
+
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        let guard = self.mutex.lock().unwrap();
+        if self.is_full() || self.is_closed() {
+            return Err(val);
+        }
+        self.put(val);
+        drop(guard);
+        Ok(())
+    }
+
+    fn poll(&self) -> Option<T> {
+        let guard = self.mutex.lock().unwrap();
+        if self.is_empty() {
+            return None;
         }
+        let val = self.take();
+        drop(guard);
+        Some(val)
     }
 
-    fn peek(&self) -> Option<T> {
+    fn peek(&self) -> Option<T> where T: Clone {
         let guard = self.mutex.lock().unwrap();
         let result = if self.is_empty() {
             None
         } else {
             unsafe {
                 let head = self.data.ptr().offset(self.head() as isize);
-                Some(ptr::read(head))
+                Some((*head).clone())
             }
         };
         drop(guard);
@@ -161,11 +322,7 @@ impl <T: PartialEq> ArrayBlockingQueueInner<T> {
         let mask = self.capacity() - 1;
         let tail = (self.head() + self.size()) & mask;
         while next != tail && !find {
-            let v = unsafe {
-                let p = self.data.ptr().offset(next as isize);
-                ptr::read(p)
-            };
-            find = v == val;
+            find = unsafe { *self.data.ptr().offset(next as isize) == val };
             next = next_node_index(next, mask);
         }
         drop(guard);
@@ -175,12 +332,21 @@ impl <T: PartialEq> ArrayBlockingQueueInner<T> {
 
 /// Bounded blocking queue is based on raw vector implementation
 /// Current implementation is based on one Mutex and two Condvars
-#[derive(Clone)]
 pub struct ArrayBlockingQueue<T> {
     inner: Arc<ArrayBlockingQueueInner<T>>
 }
 
-impl <T: PartialEq> ArrayBlockingQueue<T> {
+impl <T> Clone for ArrayBlockingQueue<T> {
+
+    /// Return new handle of the same queue
+    fn clone(&self) -> ArrayBlockingQueue<T> {
+        ArrayBlockingQueue {
+            inner: self.inner.clone()
+        }
+    }
+}
+
+impl <T> ArrayBlockingQueue<T> {
 
     /// Create queue with default capacity
     /// which is 16
@@ -201,6 +367,15 @@ impl <T: PartialEq> ArrayBlockingQueue<T> {
     pub fn remaining_capacity(&self) -> usize {
         self.inner.remaining_capacity()
     }
+
+    /// Split queue into sending and receiving halves, see `channel`
+    ///
+    /// Clones of the queue made before the split are not counted as handles, so dropping them
+    /// does not close the queue, but calling `close` on any of them disconnects all senders
+    /// and receivers
+    pub fn split(self) -> (Sender<T>, Receiver<T>) {
+        channel::split(self)
+    }
 }
 
 impl <T> BlockingQueue<T> for ArrayBlockingQueue<T> {
@@ -217,13 +392,15 @@ impl <T> BlockingQueue<T> for ArrayBlockingQueue<T> {
 
     /// Enqueue value into queue
     /// Could be blocked until dequeue event if queue is full
-    fn enqueue(&self, val: T) {
-        self.inner.enqueue(val);
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        self.inner.enqueue(val)
     }
 
     /// Dequeue value from queue
     /// Could be blocked until enqueue event if queue is empty
-    fn dequeue(&self) -> T {
+    /// Return error if queue is closed and all values were dequeued
+    fn dequeue(&self) -> Result<T, Closed> {
         self.inner.dequeue()
     }
 
@@ -234,10 +411,72 @@ impl <T> BlockingQueue<T> for ArrayBlockingQueue<T> {
         self.inner.offer(val)
     }
 
-    /// Peek queue head value without removing it from queue
-    fn peek(&self) -> Option<T> {
+    /// Enqueue value into queue if it is not full and not closed
+    /// otherwise return the value back
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        self.inner.try_enqueue(val)
+    }
+
+    /// Dequeue value from queue if it is not empty
+    fn poll(&self) -> Option<T> {
+        self.inner.poll()
+    }
+
+    /// Move up to max values from queue into target vector without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        self.inner.drain_to(target, max)
+    }
+
+    /// Enqueue all values holding the lock and notifying waiting threads once
+    /// while there is free space in queue, could be blocked until dequeue event if queue is full
+    /// Return values which were not enqueued if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        self.inner.enqueue_all(iter.into_iter())
+    }
+
+    /// Offer value into queue waiting up to specified timeout for free space
+    /// Return the value back if queue is still full when timeout elapses
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.inner.offer_timeout(val, timeout)
+    }
+
+    /// Dequeue value from queue waiting up to specified timeout for enqueue event
+    /// Return None if queue is still empty when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.inner.poll_timeout(timeout)
+    }
+
+    /// Clone queue head value without removing it from queue
+    fn peek(&self) -> Option<T> where T: Clone {
         self.inner.peek()
     }
+
+    /// Close queue, all further enqueues are rejected
+    /// Threads blocked on the queue are woken up, values left in the queue could be dequeued
+    fn close(&self) {
+        self.inner.close();
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+}
+
+impl <T> Selectable for ArrayBlockingQueue<T> {
+
+    fn is_ready(&self, op: Operation) -> bool {
+        self.inner.is_ready(op)
+    }
+
+    fn watch(&self, signal: &Arc<Signal>) {
+        self.inner.watch(signal);
+    }
+
+    fn unwatch(&self, signal: &Arc<Signal>) {
+        self.inner.unwatch(signal);
+    }
 }
 
 impl <T:PartialEq> ArrayBlockingQueue<T> {
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use super::{ArrayBlockingQueue, BlockingQueue, Closed};
+use super::select::{Operation, Selectable, Signal};
+
+/// Number of live handles of each side of a channel
+struct Handles {
+    senders: AtomicUsize,
+    receivers: AtomicUsize
+}
+
+/// Create bounded channel backed by `ArrayBlockingQueue` with specified capacity
+///
+/// Senders and receivers could be cloned. When all senders are dropped receivers
+/// get values left in the channel and then `Closed` error. When all receivers are dropped
+/// senders get their values back
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    split(ArrayBlockingQueue::with_capacity(capacity))
+}
+
+pub fn split<T>(queue: ArrayBlockingQueue<T>) -> (Sender<T>, Receiver<T>) {
+    let handles = Arc::new(Handles {
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1)
+    });
+    let sender = Sender {
+        queue: queue.clone(),
+        handles: handles.clone()
+    };
+    let receiver = Receiver {
+        queue: queue,
+        handles: handles
+    };
+    (sender, receiver)
+}
+
+/// Sending half of a channel
+pub struct Sender<T> {
+    queue: ArrayBlockingQueue<T>,
+    handles: Arc<Handles>
+}
+
+impl <T> Sender<T> {
+
+    /// Send value into channel
+    /// Could be blocked until receive event if channel is full
+    /// Return the value back if all receivers are dropped
+    pub fn send(&self, val: T) -> Result<(), T> {
+        self.queue.enqueue(val)
+    }
+
+    /// Send value into channel if it is not full
+    /// otherwise or if all receivers are dropped return the value back
+    pub fn try_send(&self, val: T) -> Result<(), T> {
+        self.queue.try_enqueue(val)
+    }
+
+    /// Send value into channel waiting up to specified timeout for free space
+    /// Return the value back if channel is still full when timeout elapses
+    pub fn send_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.queue.offer_timeout(val, timeout)
+    }
+
+    /// Return number of values in channel
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check if channel is empty
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Check if all receivers are dropped
+    pub fn is_disconnected(&self) -> bool {
+        self.handles.receivers.load(Ordering::Acquire) == 0
+    }
+}
+
+impl <T> Selectable for Sender<T> {
+
+    fn is_ready(&self, op: Operation) -> bool {
+        self.queue.is_ready(op)
+    }
+
+    fn watch(&self, signal: &Arc<Signal>) {
+        self.queue.watch(signal);
+    }
+
+    fn unwatch(&self, signal: &Arc<Signal>) {
+        self.queue.unwatch(signal);
+    }
+}
+
+impl <T> Clone for Sender<T> {
+
+    fn clone(&self) -> Sender<T> {
+        self.handles.senders.fetch_add(1, Ordering::Relaxed);
+        Sender {
+            queue: self.queue.clone(),
+            handles: self.handles.clone()
+        }
+    }
+}
+
+impl <T> Drop for Sender<T> {
+
+    fn drop(&mut self) {
+        if self.handles.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.close();
+        }
+    }
+}
+
+/// Receiving half of a channel
+pub struct Receiver<T> {
+    queue: ArrayBlockingQueue<T>,
+    handles: Arc<Handles>
+}
+
+impl <T> Receiver<T> {
+
+    /// Receive value from channel
+    /// Could be blocked until send event if channel is empty
+    /// Return error if all senders are dropped and all values were received
+    pub fn recv(&self) -> Result<T, Closed> {
+        self.queue.dequeue()
+    }
+
+    /// Receive value from channel if it is not empty
+    pub fn try_recv(&self) -> Option<T> {
+        self.queue.poll()
+    }
+
+    /// Receive value from channel waiting up to specified timeout for send event
+    /// Return None if channel is still empty when timeout elapses or all senders are dropped
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        self.queue.poll_timeout(timeout)
+    }
+
+    /// Return iterator which receives values until all senders are dropped
+    pub fn iter(&self) -> Iter<T> {
+        Iter {
+            receiver: self
+        }
+    }
+
+    /// Return number of values in channel
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check if channel is empty
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Check if all senders are dropped
+    pub fn is_disconnected(&self) -> bool {
+        self.handles.senders.load(Ordering::Acquire) == 0
+    }
+}
+
+impl <T> Selectable for Receiver<T> {
+
+    fn is_ready(&self, op: Operation) -> bool {
+        self.queue.is_ready(op)
+    }
+
+    fn watch(&self, signal: &Arc<Signal>) {
+        self.queue.watch(signal);
+    }
+
+    fn unwatch(&self, signal: &Arc<Signal>) {
+        self.queue.unwatch(signal);
+    }
+}
+
+impl <T> Clone for Receiver<T> {
+
+    fn clone(&self) -> Receiver<T> {
+        self.handles.receivers.fetch_add(1, Ordering::Relaxed);
+        Receiver {
+            queue: self.queue.clone(),
+            handles: self.handles.clone()
+        }
+    }
+}
+
+impl <T> Drop for Receiver<T> {
+
+    fn drop(&mut self) {
+        if self.handles.receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.queue.close();
+        }
+    }
+}
+
+/// Blocking iterator over values received from channel
+pub struct Iter<'a, T: 'a> {
+    receiver: &'a Receiver<T>
+}
+
+impl <'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl <'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,291 @@
+use std::cmp;
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::sync::{Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{BlockingQueue, Closed};
+
+/// Value which could be dequeued from `DelayQueue` only after its deadline
+pub trait Delayed {
+
+    fn deadline(&self) -> Instant;
+}
+
+/// Value together with deadline after which it is released
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delay<T> {
+    pub value: T,
+    pub deadline: Instant
+}
+
+impl <T> Delay<T> {
+
+    /// Release value after specified delay from now
+    pub fn after(value: T, delay: Duration) -> Delay<T> {
+        Delay {
+            value: value,
+            deadline: Instant::now() + delay
+        }
+    }
+
+    /// Release value at specified deadline
+    pub fn at(value: T, deadline: Instant) -> Delay<T> {
+        Delay {
+            value: value,
+            deadline: deadline
+        }
+    }
+}
+
+impl <T> Delayed for Delay<T> {
+
+    fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+/// Heap entry which puts the earliest deadline on top
+struct Entry<T>(T);
+
+impl <T: Delayed> PartialEq for Entry<T> {
+
+    fn eq(&self, other: &Entry<T>) -> bool {
+        self.0.deadline() == other.0.deadline()
+    }
+}
+
+impl <T: Delayed> Eq for Entry<T> { }
+
+impl <T: Delayed> PartialOrd for Entry<T> {
+
+    fn partial_cmp(&self, other: &Entry<T>) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl <T: Delayed> Ord for Entry<T> {
+
+    fn cmp(&self, other: &Entry<T>) -> CmpOrdering {
+        other.0.deadline().cmp(&self.0.deadline())
+    }
+}
+
+/// Unbounded blocking queue which releases values when their deadlines pass
+///
+/// Values are dequeued in order of their deadlines. Consumers sleep until the earliest
+/// deadline and are woken up when a value with an earlier deadline is enqueued
+pub struct DelayQueue<T> {
+    heap: Mutex<BinaryHeap<Entry<T>>>,
+    closed: AtomicBool,
+    available: Condvar
+}
+
+impl <T: Delayed> DelayQueue<T> {
+
+    /// Create empty queue
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue {
+            heap: Mutex::new(BinaryHeap::new()),
+            closed: AtomicBool::new(false),
+            available: Condvar::new()
+        }
+    }
+
+    /// Return number of values which deadlines have passed
+    pub fn expired(&self) -> usize {
+        let heap = self.heap.lock().unwrap();
+        let now = Instant::now();
+        let expired = heap.iter().filter(|entry| entry.0.deadline() <= now).count();
+        drop(heap);
+        expired
+    }
+
+    /// Push value and wake up consumers if it became the earliest one
+    /// should be called under the lock
+    fn push(&self, heap: &mut BinaryHeap<Entry<T>>, val: T) {
+        let earliest = match heap.peek() {
+            Some(head) => val.deadline() < head.0.deadline(),
+            None => true,
+        };
+        heap.push(Entry(val));
+        if earliest {
+            self.available.notify_all();
+        }
+    }
+
+    /// Pop the earliest value if its deadline has passed
+    fn pop_expired(&self, heap: &mut BinaryHeap<Entry<T>>) -> Option<T> {
+        let expired = match heap.peek() {
+            Some(head) => head.0.deadline() <= Instant::now(),
+            None => false,
+        };
+        if expired {
+            heap.pop().map(|entry| entry.0)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Wait until the earliest value expires, queue is closed and empty or deadline is reached
+    fn take_before(&self, deadline: Option<Instant>) -> Option<T> {
+        let mut heap = self.heap.lock().unwrap();
+        loop {
+            if let Some(val) = self.pop_expired(&mut heap) {
+                return Some(val);
+            }
+            if heap.is_empty() && self.is_closed() {
+                return None;
+            }
+            let now = Instant::now();
+            let wake_up = match (heap.peek().map(|head| head.0.deadline()), deadline) {
+                (Some(earliest), Some(deadline)) => Some(cmp::min(earliest, deadline)),
+                (Some(earliest), None) => Some(earliest),
+                (None, deadline) => deadline,
+            };
+            if let Some(deadline) = deadline {
+                if now >= deadline {
+                    return None;
+                }
+            }
+            heap = match wake_up {
+                Some(wake_up) => self.wait_until(heap, wake_up, now),
+                None => self.available.wait(heap).unwrap(),
+            };
+        }
+    }
+
+    fn wait_until<'a>(&self, heap: MutexGuard<'a, BinaryHeap<Entry<T>>>, wake_up: Instant, now: Instant) -> MutexGuard<'a, BinaryHeap<Entry<T>>> {
+        if wake_up <= now {
+            return heap;
+        }
+        self.available.wait_timeout(heap, wake_up - now).unwrap().0
+    }
+}
+
+impl <T: Delayed> BlockingQueue<T> for DelayQueue<T> {
+
+    /// Return number of values in queue including values which deadlines have not passed yet
+    fn len(&self) -> usize {
+        self.heap.lock().unwrap().len()
+    }
+
+    /// Check if queue has no values including values which deadlines have not passed yet
+    fn is_empty(&self) -> bool {
+        self.heap.lock().unwrap().is_empty()
+    }
+
+    /// Enqueue value into queue
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        let mut heap = self.heap.lock().unwrap();
+        if self.is_closed() {
+            return Err(val);
+        }
+        self.push(&mut heap, val);
+        drop(heap);
+        Ok(())
+    }
+
+    /// Dequeue value with the earliest deadline
+    /// Could be blocked until its deadline or until enqueue event if queue is empty
+    /// Return error if queue is closed and all values were dequeued
+    fn dequeue(&self) -> Result<T, Closed> {
+        self.take_before(None).ok_or(Closed)
+    }
+
+    /// Offer value into queue
+    /// always return true unless queue is closed due to unbound capacity
+    fn offer(&self, val: T) -> bool {
+        self.enqueue(val).is_ok()
+    }
+
+    /// Enqueue value into queue
+    /// fails only if queue is closed due to unbound capacity
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        self.enqueue(val)
+    }
+
+    /// Dequeue value with the earliest deadline if the deadline has passed
+    fn poll(&self) -> Option<T> {
+        let mut heap = self.heap.lock().unwrap();
+        let result = self.pop_expired(&mut heap);
+        drop(heap);
+        result
+    }
+
+    /// Move up to max values which deadlines have passed into target vector without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut heap = self.heap.lock().unwrap();
+        let mut count = 0;
+        while count < max {
+            match self.pop_expired(&mut heap) {
+                Some(val) => target.push(val),
+                None => break,
+            }
+            count += 1;
+        }
+        drop(heap);
+        count
+    }
+
+    /// Enqueue all values holding the lock and notifying waiting threads once
+    /// Return all values back if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut heap = self.heap.lock().unwrap();
+        if self.is_closed() {
+            return Err(iter.into_iter().collect());
+        }
+        let earliest = heap.peek().map(|head| head.0.deadline());
+        heap.extend(iter.into_iter().map(Entry));
+        let changed = match (heap.peek(), earliest) {
+            (Some(head), Some(earliest)) => head.0.deadline() < earliest,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if changed {
+            self.available.notify_all();
+        }
+        drop(heap);
+        Ok(())
+    }
+
+    /// Offer value into queue
+    /// never times out due to unbound capacity
+    fn offer_timeout(&self, val: T, _timeout: Duration) -> Result<(), T> {
+        self.enqueue(val)
+    }
+
+    /// Dequeue value with the earliest deadline waiting up to specified timeout for the deadline
+    /// Return None if no deadline has passed when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.take_before(Some(Instant::now() + timeout))
+    }
+
+    /// Clone value with the earliest deadline without removing it from queue,
+    /// the deadline could be not passed yet
+    fn peek(&self) -> Option<T> where T: Clone {
+        let heap = self.heap.lock().unwrap();
+        let result = heap.peek().map(|head| head.0.clone());
+        drop(heap);
+        result
+    }
+
+    /// Close queue, all further enqueues are rejected
+    /// Threads blocked on empty queue are woken up, values left in the queue are released
+    /// when their deadlines pass
+    fn close(&self) {
+        let heap = self.heap.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.available.notify_all();
+        drop(heap);
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,439 @@
+use std::ptr;
+use std::cmp;
+
+use std::boxed::Box;
+
+use std::sync::{Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use std::time::{Duration, Instant};
+
+use super::{BlockingQueue, Closed};
+use super::node::DequeNode;
+
+/// End of deque which operation works with
+#[derive(Clone, Copy)]
+enum End {
+    Front,
+    Back
+}
+
+/// Doubly linked list of deque, should be accessed only under the lock
+struct Links<T> {
+    head: *mut DequeNode<T>,
+    tail: *mut DequeNode<T>,
+    len: usize
+}
+
+// nodes are reachable only through the lock of deque
+unsafe impl <T: Send> Send for Links<T> { }
+
+impl <T> Links<T> {
+
+    fn new() -> Links<T> {
+        Links {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            len: 0
+        }
+    }
+
+    fn push(&mut self, end: End, val: T) {
+        let node = DequeNode::boxed(val);
+        unsafe {
+            match end {
+                End::Front => {
+                    (*node).next = self.head;
+                    if self.head.is_null() {
+                        self.tail = node;
+                    }
+                    else {
+                        (*self.head).prev = node;
+                    }
+                    self.head = node;
+                },
+                End::Back => {
+                    (*node).prev = self.tail;
+                    if self.tail.is_null() {
+                        self.head = node;
+                    }
+                    else {
+                        (*self.tail).next = node;
+                    }
+                    self.tail = node;
+                },
+            }
+        }
+        self.len += 1;
+    }
+
+    fn pop(&mut self, end: End) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = unsafe {
+            match end {
+                End::Front => {
+                    let node = Box::from_raw(self.head);
+                    self.head = node.next;
+                    if self.head.is_null() {
+                        self.tail = ptr::null_mut();
+                    }
+                    else {
+                        (*self.head).prev = ptr::null_mut();
+                    }
+                    node
+                },
+                End::Back => {
+                    let node = Box::from_raw(self.tail);
+                    self.tail = node.prev;
+                    if self.tail.is_null() {
+                        self.head = ptr::null_mut();
+                    }
+                    else {
+                        (*self.tail).next = ptr::null_mut();
+                    }
+                    node
+                },
+            }
+        };
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    fn peek(&self, end: End) -> Option<T> where T: Clone {
+        let node = match end {
+            End::Front => self.head,
+            End::Back => self.tail,
+        };
+        if node.is_null() {
+            None
+        }
+        else {
+            unsafe { Some((*node).value.clone()) }
+        }
+    }
+}
+
+impl <T> Drop for Links<T> {
+
+    fn drop(&mut self) {
+        while self.pop(End::Front).is_some() { }
+    }
+}
+
+/// Blocking double ended queue based on doubly linked list
+///
+/// Values could be pushed and popped at both ends, so producers could put urgent work
+/// at the front and a worker could take its own work from the back while other threads
+/// steal from the front. Deque is unbounded unless it is created with capacity.
+/// Current implementation is based on one Mutex and two Condvars
+pub struct LinkedBlockingDeque<T> {
+    links: Mutex<Links<T>>,
+    capacity: Option<usize>,
+    closed: AtomicBool,
+    empty: Condvar,
+    full: Condvar
+}
+
+impl <T> LinkedBlockingDeque<T> {
+
+    /// Create unbounded deque
+    pub fn new() -> LinkedBlockingDeque<T> {
+        LinkedBlockingDeque::create(None)
+    }
+
+    /// Create deque bounded by capacity
+    pub fn with_capacity(capacity: usize) -> LinkedBlockingDeque<T> {
+        LinkedBlockingDeque::create(Some(capacity))
+    }
+
+    fn create(capacity: Option<usize>) -> LinkedBlockingDeque<T> {
+        assert!(capacity != Some(0), "capacity of bounded deque should be greater than zero");
+        LinkedBlockingDeque {
+            links: Mutex::new(Links::new()),
+            capacity: capacity,
+            closed: AtomicBool::new(false),
+            empty: Condvar::new(),
+            full: Condvar::new()
+        }
+    }
+
+    /// Return capacity of bounded deque or None if deque is unbounded
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Push value at the front if deque is not full and not closed
+    /// otherwise return the value back
+    pub fn push_front(&self, val: T) -> Result<(), T> {
+        self.try_insert(End::Front, val)
+    }
+
+    /// Push value at the back if deque is not full and not closed
+    /// otherwise return the value back
+    pub fn push_back(&self, val: T) -> Result<(), T> {
+        self.try_insert(End::Back, val)
+    }
+
+    /// Pop value from the front if deque is not empty
+    pub fn pop_front(&self) -> Option<T> {
+        self.try_remove(End::Front)
+    }
+
+    /// Pop value from the back if deque is not empty
+    pub fn pop_back(&self) -> Option<T> {
+        self.try_remove(End::Back)
+    }
+
+    /// Put value at the front
+    /// Could be blocked until removal event if bounded deque is full
+    /// Return the value back if deque is closed
+    pub fn put_first(&self, val: T) -> Result<(), T> {
+        self.insert_before(End::Front, val, None)
+    }
+
+    /// Put value at the back
+    /// Could be blocked until removal event if bounded deque is full
+    /// Return the value back if deque is closed
+    pub fn put_last(&self, val: T) -> Result<(), T> {
+        self.insert_before(End::Back, val, None)
+    }
+
+    /// Take value from the front
+    /// Could be blocked until insert event if deque is empty
+    /// Return error if deque is closed and all values were taken
+    pub fn take_first(&self) -> Result<T, Closed> {
+        self.remove_before(End::Front, None).ok_or(Closed)
+    }
+
+    /// Take value from the back
+    /// Could be blocked until insert event if deque is empty
+    /// Return error if deque is closed and all values were taken
+    pub fn take_last(&self) -> Result<T, Closed> {
+        self.remove_before(End::Back, None).ok_or(Closed)
+    }
+
+    /// Clone value at the front without removing it from deque
+    pub fn peek_front(&self) -> Option<T> where T: Clone {
+        self.peek_at(End::Front)
+    }
+
+    /// Clone value at the back without removing it from deque
+    pub fn peek_back(&self) -> Option<T> where T: Clone {
+        self.peek_at(End::Back)
+    }
+
+    fn is_full(&self, links: &Links<T>) -> bool {
+        match self.capacity {
+            Some(capacity) => links.len >= capacity,
+            None => false,
+        }
+    }
+
+    /// Wait while deque is full and not closed, return None if deque is closed
+    /// or if deadline is reached
+    fn wait_not_full<'a>(&self, mut links: MutexGuard<'a, Links<T>>, deadline: Option<Instant>) -> Option<MutexGuard<'a, Links<T>>> {
+        while self.is_full(&links) && !self.is_closed() {
+            match deadline {
+                None => links = self.full.wait(links).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    links = self.full.wait_timeout(links, deadline - now).unwrap().0;
+                }
+            }
+        }
+        if self.is_closed() {
+            return None;
+        }
+        Some(links)
+    }
+
+    /// Wait while deque is empty and not closed, return None if deque is closed and empty
+    /// or if deadline is reached
+    fn wait_not_empty<'a>(&self, mut links: MutexGuard<'a, Links<T>>, deadline: Option<Instant>) -> Option<MutexGuard<'a, Links<T>>> {
+        while links.len == 0 && !self.is_closed() {
+            match deadline {
+                None => links = self.empty.wait(links).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    links = self.empty.wait_timeout(links, deadline - now).unwrap().0;
+                }
+            }
+        }
+        if links.len == 0 {
+            return None;
+        }
+        Some(links)
+    }
+
+    fn try_insert(&self, end: End, val: T) -> Result<(), T> {
+        let mut links = self.links.lock().unwrap();
+        if self.is_full(&links) || self.is_closed() {
+            return Err(val);
+        }
+        links.push(end, val);
+        self.empty.notify_one();
+        drop(links);
+        Ok(())
+    }
+
+    fn try_remove(&self, end: End) -> Option<T> {
+        let mut links = self.links.lock().unwrap();
+        let val = links.pop(end);
+        if val.is_some() {
+            self.full.notify_one();
+        }
+        drop(links);
+        val
+    }
+
+    fn insert_before(&self, end: End, val: T, deadline: Option<Instant>) -> Result<(), T> {
+        let links = self.links.lock().unwrap();
+        let mut links = match self.wait_not_full(links, deadline) {
+            Some(links) => links,
+            None => return Err(val),
+        };
+        links.push(end, val);
+        self.empty.notify_one();
+        drop(links);
+        Ok(())
+    }
+
+    fn remove_before(&self, end: End, deadline: Option<Instant>) -> Option<T> {
+        let links = self.links.lock().unwrap();
+        let mut links = match self.wait_not_empty(links, deadline) {
+            Some(links) => links,
+            None => return None,
+        };
+        let val = links.pop(end);
+        self.full.notify_one();
+        drop(links);
+        val
+    }
+
+    fn peek_at(&self, end: End) -> Option<T> where T: Clone {
+        let links = self.links.lock().unwrap();
+        let result = links.peek(end);
+        drop(links);
+        result
+    }
+}
+
+impl <T> BlockingQueue<T> for LinkedBlockingDeque<T> {
+
+    /// Return size of current deque
+    fn len(&self) -> usize {
+        self.links.lock().unwrap().len
+    }
+
+    /// Check if current deque is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Put value at the back, same as `put_last`
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        self.put_last(val)
+    }
+
+    /// Take value from the front, same as `take_first`
+    fn dequeue(&self) -> Result<T, Closed> {
+        self.take_first()
+    }
+
+    /// Offer value at the back
+    /// If deque is not full and not closed return true otherwise false
+    fn offer(&self, val: T) -> bool {
+        self.push_back(val).is_ok()
+    }
+
+    /// Push value at the back, same as `push_back`
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        self.push_back(val)
+    }
+
+    /// Pop value from the front, same as `pop_front`
+    fn poll(&self) -> Option<T> {
+        self.pop_front()
+    }
+
+    /// Move up to max values from the front into target vector without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut links = self.links.lock().unwrap();
+        let count = cmp::min(links.len, max);
+        target.reserve(count);
+        for _ in 0..count {
+            if let Some(val) = links.pop(End::Front) {
+                target.push(val);
+            }
+        }
+        if count > 0 {
+            self.full.notify_all();
+        }
+        drop(links);
+        count
+    }
+
+    /// Put all values at the back holding the lock and notifying waiting threads once
+    /// while there is free space in deque, could be blocked until removal event if bounded deque is full
+    /// Return values which were not enqueued if deque is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut iter = iter.into_iter().peekable();
+        let mut links = self.links.lock().unwrap();
+        while iter.peek().is_some() {
+            links = match self.wait_not_full(links, None) {
+                Some(links) => links,
+                None => return Err(iter.collect()),
+            };
+            while !self.is_full(&links) {
+                match iter.next() {
+                    Some(val) => links.push(End::Back, val),
+                    None => break,
+                }
+            }
+            self.empty.notify_all();
+        }
+        drop(links);
+        Ok(())
+    }
+
+    /// Offer value at the back waiting up to specified timeout for free space
+    /// Return the value back if deque is still full when timeout elapses
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.insert_before(End::Back, val, Some(Instant::now() + timeout))
+    }
+
+    /// Take value from the front waiting up to specified timeout for insert event
+    /// Return None if deque is still empty when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.remove_before(End::Front, Some(Instant::now() + timeout))
+    }
+
+    /// Clone value at the front, same as `peek_front`
+    fn peek(&self) -> Option<T> where T: Clone {
+        self.peek_front()
+    }
+
+    /// Close deque, all further inserts are rejected
+    /// Threads blocked on the deque are woken up, values left in the deque could be taken
+    fn close(&self) {
+        let links = self.links.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.empty.notify_all();
+        self.full.notify_all();
+        drop(links);
+    }
+
+    /// Check if deque is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
@@ -1,4 +1,4 @@
-use std::ptr;
+use std::cmp;
 
 use std::boxed::Box;
 
@@ -6,74 +6,14 @@ use std::cmp::PartialEq;
 
 use std::option::Option;
 
-use std::ops::Deref;
-use std::ops::DerefMut;
+use std::sync::{Arc, Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use std::clone::Clone;
-use std::marker::Copy;
+use std::time::{Duration, Instant};
 
-use std::sync::{Mutex, MutexGuard, Condvar};
-use std::sync::atomic::{AtomicUsize,Ordering};
-
-struct Node<T> {
-    value: Option<T>,
-    next: Option<Link<T>>
-}
-
-impl <T> Node<T> {
-
-    fn empty() -> Node<T> {
-        Node {
-            value: None,
-            next: None
-        }
-    }
-
-    fn non_empty(value: T) -> Node<T> {
-        Node {
-            value: Some(value),
-            next: None
-        }
-    }
-}
-
-struct Link<T> {
-    ptr: *mut Node<T>
-}
-
-impl <T> Link<T> {
-
-    fn new(node: Node<T>) -> Link<T> {
-        Link {
-            ptr: Box::into_raw(Box::new(node))
-        }
-    }
-}
-
-impl <T> Deref for Link<T> {
-    type Target = Node<T>;
-
-    fn deref(&self) -> &Node<T> {
-        unsafe { &*self.ptr }
-    }
-}
-
-impl <T> DerefMut for Link<T> {
-
-    fn deref_mut(&mut self) -> &mut Node<T> {
-        unsafe { &mut *self.ptr }
-    }
-}
-
-impl<T> Clone for Link<T> {
-
-    fn clone(&self) -> Link<T> {
-        Link { ptr: self.ptr }
-    }
-}
-
-impl <T> Copy for Link<T> { }
-unsafe impl <T: Send> Send for Link<T> { }
+use super::{BlockingQueue, Closed};
+use super::select::{Observers, Operation, Selectable, Signal};
+use super::node::{Node, Link};
 
 /// Unbounded Blocking Queue base on linked list
 /// 
@@ -83,18 +23,22 @@ pub struct UnboundedBlockingQueue<T> {
     head: Mutex<Link<T>>,
     tail: Mutex<Link<T>>,
     size: AtomicUsize,
-    empty: Condvar
+    closed: AtomicBool,
+    empty: Condvar,
+    observers: Observers
 }
 
-impl <T: PartialEq> UnboundedBlockingQueue<T> {
+impl <T> UnboundedBlockingQueue<T> {
 
     pub fn new() -> UnboundedBlockingQueue<T> {
         let empty = Link::new(Node::empty());
         UnboundedBlockingQueue {
             size: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
             head: Mutex::new(empty),
             tail: Mutex::new(empty),
-            empty: Condvar::new()
+            empty: Condvar::new(),
+            observers: Observers::new()
         }
     }
 
@@ -103,68 +47,244 @@ impl <T: PartialEq> UnboundedBlockingQueue<T> {
         self.size.load(Ordering::Relaxed)
     }
 
-    /// Return true if current queue is empty
+    /// Return true if current queue is empty, same as `BlockingQueue::is_empty`
     pub fn is_empty(&self) -> bool {
+        BlockingQueue::is_empty(self)
+    }
+
+    /// Enqueue value into queue, same as `BlockingQueue::enqueue`
+    pub fn enqueue(&self, val: T) -> Result<(), T> {
+        BlockingQueue::enqueue(self, val)
+    }
+
+    /// Dequeue value from queue, same as `BlockingQueue::dequeue`
+    pub fn dequeue(&self) -> Result<T, Closed> {
+        BlockingQueue::dequeue(self)
+    }
+
+    /// Offer value into queue, same as `BlockingQueue::offer`
+    pub fn offer(&self, val: T) -> bool {
+        BlockingQueue::offer(self, val)
+    }
+
+    /// Clone head of queue without removing it from queue, same as `BlockingQueue::peek`
+    pub fn peek(&self) -> Option<T> where T: Clone {
+        BlockingQueue::peek(self)
+    }
+
+    /// Notify threads waiting for value under the head lock
+    /// so that notification could not be lost between their check and wait
+    fn signal_not_empty(&self) {
+        let head = self.head.lock().unwrap();
+        self.empty.notify_all();
+        self.observers.notify();
+        drop(head);
+    }
+}
+
+impl <T: PartialEq> UnboundedBlockingQueue<T> {
+
+    /// Check if current queue contains value
+    pub fn contains(&self, val: T) -> bool {
+        let mut head_lock = self.head.lock().unwrap();
+        let tail_lock = self.tail.lock().unwrap();
+        let find = contains(val, &mut head_lock);
+        drop(tail_lock);
+        drop(head_lock);
+        find
+    }
+}
+
+impl <T> BlockingQueue<T> for UnboundedBlockingQueue<T> {
+
+    /// Return size of current queue
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    /// Return true if current queue is empty
+    fn is_empty(&self) -> bool {
         self.size() == 0
     }
 
     /// Enqueue value into queue
     /// Notify all threads that wait for dequeue value from queue
-    pub fn enqueue(&self, val: T) {
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
         let mut tail = self.tail.lock().unwrap();
-        put(Node::non_empty(val), &mut tail);
-        let current_size = self.size.fetch_add(1, Ordering::Relaxed);
-        if current_size + 1 > 0 {
-            self.empty.notify_all();
+        if self.is_closed() {
+            return Err(val);
         }
+        put(Node::non_empty(val), &mut tail);
+        self.size.fetch_add(1, Ordering::Relaxed);
+        drop(tail);
+        self.signal_not_empty();
+        Ok(())
     }
 
     /// Dequeue value from queue
     /// Could be blocked on Condvar if queue is empty
-    pub fn dequeue(&self) -> T {
+    /// Return error if queue is closed and all values were dequeued
+    fn dequeue(&self) -> Result<T, Closed> {
         let mut head = self.head.lock().unwrap();
-        while self.is_empty() {
+        while self.is_empty() && !self.is_closed() {
             head = self.empty.wait(head).unwrap();
         }
+        if self.is_empty() {
+            return Err(Closed);
+        }
         let val = take(&mut head);
         self.size.fetch_sub(1, Ordering::Relaxed);
-        val
+        Ok(val)
     }
 
-    /// Check if current queue contains value
-    pub fn contains(&self, val: T) -> bool {
-        let mut head_lock = self.head.lock().unwrap();
-        let tail_lock = self.tail.lock().unwrap();
-        let find = contains(val, &mut head_lock);
-        drop(tail_lock);
-        drop(head_lock);
-        find
+    /// Offer value into queue
+    /// always return true unless queue is closed due to unbound capacity
+    fn offer(&self, val: T) -> bool {
+        self.enqueue(val).is_ok()
+    }
+
+    /// Enqueue value into queue
+    /// fails only if queue is closed due to unbound capacity
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        self.enqueue(val)
+    }
+
+    /// Enqueue all values holding the tail lock and notifying waiting threads once
+    /// Return all values back if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut tail = self.tail.lock().unwrap();
+        if self.is_closed() {
+            return Err(iter.into_iter().collect());
+        }
+        let mut count = 0;
+        for val in iter {
+            put(Node::non_empty(val), &mut tail);
+            count += 1;
+        }
+        self.size.fetch_add(count, Ordering::Relaxed);
+        drop(tail);
+        if count > 0 {
+            self.signal_not_empty();
+        }
+        Ok(())
+    }
+
+    /// Move up to max values from queue into target vector without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut head = self.head.lock().unwrap();
+        let count = cmp::min(self.size(), max);
+        target.reserve(count);
+        for _ in 0..count {
+            target.push(take(&mut head));
+        }
+        self.size.fetch_sub(count, Ordering::Relaxed);
+        count
+    }
+
+    /// Dequeue value from queue if it is not empty
+    fn poll(&self) -> Option<T> {
+        let mut head = self.head.lock().unwrap();
+        if self.is_empty() {
+            return None;
+        }
+        let val = take(&mut head);
+        self.size.fetch_sub(1, Ordering::Relaxed);
+        Some(val)
     }
 
     /// Offer value into queue
-    /// alwayes return true due to unobound capacity
-    pub fn offer(&self, val: T) -> bool {
-        self.enqueue(val);
-        true
+    /// never times out due to unbound capacity
+    fn offer_timeout(&self, val: T, _timeout: Duration) -> Result<(), T> {
+        self.enqueue(val)
     }
 
-    /// Peek head of queue with out removing it from queue
-    pub fn peek(&self) -> Option<T> {
+    /// Dequeue value from queue waiting up to specified timeout for enqueue event
+    /// Return None if queue is still empty when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        let start = Instant::now();
+        let mut head = self.head.lock().unwrap();
+        while self.is_empty() {
+            let elapsed = start.elapsed();
+            if elapsed >= timeout || self.is_closed() {
+                return None;
+            }
+            head = self.empty.wait_timeout(head, timeout - elapsed).unwrap().0;
+        }
+        let val = take(&mut head);
+        self.size.fetch_sub(1, Ordering::Relaxed);
+        Some(val)
+    }
+
+    /// Clone head of queue without removing it from queue
+    fn peek(&self) -> Option<T> where T: Clone {
         let mut head_lock = self.head.lock().unwrap();
         get(&mut head_lock)
     }
+
+    /// Close queue, all further enqueues are rejected
+    /// Threads blocked on the queue are woken up, values left in the queue could be dequeued
+    fn close(&self) {
+        let tail = self.tail.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        drop(tail);
+        self.signal_not_empty();
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
 }
 
-fn put<T: PartialEq>(node: Node<T>, last: &mut MutexGuard<Link<T>>) {
+impl <T> Selectable for UnboundedBlockingQueue<T> {
+
+    /// Send is always ready due to unbound capacity
+    fn is_ready(&self, op: Operation) -> bool {
+        match op {
+            Operation::Send => true,
+            Operation::Recv => !self.is_empty() || self.is_closed(),
+        }
+    }
+
+    /// Observers are notified under the head lock only when queue becomes not empty or closed
+    fn watch(&self, signal: &Arc<Signal>) {
+        let head = self.head.lock().unwrap();
+        self.observers.add(signal);
+        drop(head);
+    }
+
+    fn unwatch(&self, signal: &Arc<Signal>) {
+        let head = self.head.lock().unwrap();
+        self.observers.remove(signal);
+        drop(head);
+    }
+}
+
+impl <T> Drop for UnboundedBlockingQueue<T> {
+
+    fn drop(&mut self) {
+        let mut link = Some(*self.head.get_mut().unwrap());
+        while let Some(current) = link {
+            link = current.next();
+            unsafe { drop(Box::from_raw(current.ptr)); }
+        }
+    }
+}
+
+fn put<T>(node: Node<T>, last: &mut MutexGuard<Link<T>>) {
     let link = Link::new(node);
-    (***last).next = Some(link);
+    (**last).set_next(link);
     **last = link;
 }
 
-fn take<T: PartialEq>(head: &mut MutexGuard<Link<T>>) -> T {
+fn take<T>(head: &mut MutexGuard<Link<T>>) -> T {
     let h = **head;
-    let mut first = (*h).next.unwrap();
+    let mut first = h.next().unwrap();
     **head = first;
+    // old head is reachable only under head lock
+    unsafe { drop(Box::from_raw(h.ptr)); }
     (*first).value.take().unwrap()
 }
 
@@ -177,28 +297,18 @@ fn contains<T: PartialEq>(val: T, head: &mut MutexGuard<Link<T>>) -> bool {
             find = true;
             break;
         }
-        node = match (*node).next {
-            Some(ref next) => *next,
+        node = match node.next() {
+            Some(next) => next,
             None => break,
         }
     }
     find
 }
 
-fn get<T: PartialEq>(head: &mut MutexGuard<Link<T>>) -> Option<T> {
+fn get<T: Clone>(head: &mut MutexGuard<Link<T>>) -> Option<T> {
     let h = **head;
-    match (*h).next {
-        Some(mut val) => {
-            match (*val).value {
-                Some(ref mut val) => {
-                    let raw: *const _ = &mut *val;
-                    unsafe {
-                        Some(ptr::read(raw))
-                    }
-                },
-                None => None,
-            }
-        },
+    match h.next() {
+        Some(first) => (*first).value.clone(),
         None => None,
     }
 }
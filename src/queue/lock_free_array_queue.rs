@@ -0,0 +1,175 @@
+extern crate alloc;
+
+use self::alloc::raw_vec::RawVec;
+use std::ptr;
+use std::cmp;
+use std::thread;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::NonBlockingQueue;
+use super::super::round_up_to_next_highest_power_of_two;
+
+const MIN_CAPACITY: usize = 2;
+
+/// Bounded multi producer multi consumer queue based on ring buffer
+///
+/// Every slot has a sequence number which tells producers and consumers
+/// in which lap the slot could be written or read, so threads synchronize
+/// only on the slot they use and on head or tail counter.
+/// Every slot also counts readers which clone its value in peek.
+/// Push is lock-free, pop is lock-free unless another thread peeks the same value,
+/// then consumer waits until the clone is done.
+/// Values are cloned through shared references by several threads, so the queue
+/// could be shared between threads only if values are `Sync`
+pub struct LockFreeArrayQueue<T> {
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    mask: usize,
+    sequences: Vec<AtomicUsize>,
+    readers: Vec<AtomicUsize>,
+    data: RawVec<T>
+}
+
+unsafe impl <T: Send> Send for LockFreeArrayQueue<T> { }
+unsafe impl <T: Send + Sync> Sync for LockFreeArrayQueue<T> { }
+
+impl <T> LockFreeArrayQueue<T> {
+
+    /// Create queue with capacity rounded up to the next power of two
+    pub fn with_capacity(capacity: usize) -> LockFreeArrayQueue<T> {
+        let capacity = round_up_to_next_highest_power_of_two(cmp::max(capacity, MIN_CAPACITY));
+        let mut sequences = Vec::with_capacity(capacity);
+        let mut readers = Vec::with_capacity(capacity);
+        for index in 0..capacity {
+            sequences.push(AtomicUsize::new(index));
+            readers.push(AtomicUsize::new(0));
+        }
+        LockFreeArrayQueue {
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            mask: capacity - 1,
+            sequences: sequences,
+            readers: readers,
+            data: RawVec::with_capacity(capacity)
+        }
+    }
+
+    /// Return capacity of current queue
+    pub fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+}
+
+impl <T> NonBlockingQueue<T> for LockFreeArrayQueue<T> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        cmp::min(tail.wrapping_sub(head), self.capacity())
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if queue is full
+    fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Push value into queue if it is not full otherwise return the value back
+    fn try_push(&self, val: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let index = tail & self.mask;
+            let sequence = self.sequences[index].load(Ordering::Acquire);
+            let lap = sequence.wrapping_sub(tail) as isize;
+            if lap == 0 {
+                let current = self.tail.compare_and_swap(tail, tail.wrapping_add(1), Ordering::Relaxed);
+                if current == tail {
+                    unsafe { ptr::write(self.data.ptr().offset(index as isize), val); }
+                    self.sequences[index].store(tail.wrapping_add(1), Ordering::Release);
+                    return Ok(());
+                }
+                tail = current;
+            }
+            else if lap < 0 {
+                // slot still holds value of previous lap
+                return Err(val);
+            }
+            else {
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Pop value from queue if it is not empty
+    /// Waits for threads which clone the value in peek
+    fn try_pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let index = head & self.mask;
+            let sequence = self.sequences[index].load(Ordering::Acquire);
+            let lap = sequence.wrapping_sub(head.wrapping_add(1)) as isize;
+            if lap == 0 {
+                let current = self.head.compare_and_swap(head, head.wrapping_add(1), Ordering::SeqCst);
+                if current == head {
+                    // wait for threads which clone the value in peek
+                    while self.readers[index].load(Ordering::SeqCst) != 0 {
+                        thread::yield_now();
+                    }
+                    let val = unsafe { ptr::read(self.data.ptr().offset(index as isize)) };
+                    self.sequences[index].store(head.wrapping_add(self.capacity()), Ordering::Release);
+                    return Some(val);
+                }
+                head = current;
+            }
+            else if lap < 0 {
+                // slot was not written in current lap yet
+                return None;
+            }
+            else {
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Clone head value without removing it from queue
+    ///
+    /// Reader registers on the slot and then re-checks head and slot sequence,
+    /// so consumer which moves head waits until the clone is done before it reads the slot.
+    /// If head or sequence changed the read is repeated
+    fn peek(&self) -> Option<T> where T: Clone {
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let index = head & self.mask;
+            let sequence = self.sequences[index].load(Ordering::Acquire);
+            if sequence != head.wrapping_add(1) {
+                if self.head.load(Ordering::Acquire) == head {
+                    return None;
+                }
+                continue;
+            }
+            self.readers[index].fetch_add(1, Ordering::SeqCst);
+            let val = if self.head.load(Ordering::SeqCst) == head && self.sequences[index].load(Ordering::Acquire) == sequence {
+                unsafe { Some((*self.data.ptr().offset(index as isize)).clone()) }
+            }
+            else {
+                None
+            };
+            self.readers[index].fetch_sub(1, Ordering::Release);
+            if val.is_some() {
+                return val;
+            }
+        }
+    }
+}
+
+impl <T> Drop for LockFreeArrayQueue<T> {
+
+    fn drop(&mut self) {
+        while self.try_pop().is_some() { }
+    }
+}
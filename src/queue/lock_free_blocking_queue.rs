@@ -0,0 +1,315 @@
+use std::sync::{Mutex, Condvar};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use super::{BlockingQueue, NonBlockingQueue, Closed};
+use super::{LockFreeArrayQueue, LockFreeLinkedQueue, SpscArrayQueue, MpscLinkedQueue, MpscIntrusiveQueue, Linked};
+use super::super::reclaim::Reclaim;
+
+/// Blocking queue based on a lock-free queue, bounded `LockFreeArrayQueue` by default
+///
+/// Values are moved without locks, the Mutex and Condvars are used
+/// only by threads which wait on empty or full queue.
+/// Queue is shared by `&self` but on top of `SpscArrayQueue`, `MpscLinkedQueue` or
+/// `MpscIntrusiveQueue` it still allows only one producer or consumer at a time,
+/// a second thread which uses the same side at the same time panics
+pub struct LockFreeBlockingQueue<T, Q = LockFreeArrayQueue<T>> {
+    queue: Q,
+    mutex: Mutex<()>,
+    consumers: AtomicUsize,
+    producers: AtomicUsize,
+    closed: AtomicBool,
+    empty: Condvar,
+    full: Condvar,
+    value: PhantomData<T>
+}
+
+impl <T> LockFreeBlockingQueue<T, LockFreeArrayQueue<T>> {
+
+    /// Create bounded queue with capacity rounded up to the next power of two
+    pub fn with_capacity(capacity: usize) -> LockFreeBlockingQueue<T, LockFreeArrayQueue<T>> {
+        LockFreeBlockingQueue::from_queue(LockFreeArrayQueue::with_capacity(capacity))
+    }
+
+    /// Return capacity of current queue
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+}
+
+impl <T: Send + 'static, R: Reclaim> LockFreeBlockingQueue<T, LockFreeLinkedQueue<T, R>> {
+
+    /// Create unbounded queue, producers never wait and consumers are parked
+    /// only when queue is empty
+    pub fn unbounded() -> LockFreeBlockingQueue<T, LockFreeLinkedQueue<T, R>> {
+        LockFreeBlockingQueue::from_queue(LockFreeLinkedQueue::new())
+    }
+}
+
+impl <T> LockFreeBlockingQueue<T, SpscArrayQueue<T>> {
+
+    /// Create bounded queue for one producer and one consumer
+    /// with capacity rounded up to the next power of two
+    ///
+    /// Enqueue and dequeue operations panic when two threads use the same side at the same time
+    pub fn spsc(capacity: usize) -> LockFreeBlockingQueue<T, SpscArrayQueue<T>> {
+        LockFreeBlockingQueue::from_queue(SpscArrayQueue::with_capacity(capacity))
+    }
+}
+
+impl <T> LockFreeBlockingQueue<T, MpscLinkedQueue<T>> {
+
+    /// Create unbounded queue for many producers and one consumer
+    ///
+    /// Dequeue operations panic when two threads dequeue at the same time
+    pub fn mpsc() -> LockFreeBlockingQueue<T, MpscLinkedQueue<T>> {
+        LockFreeBlockingQueue::from_queue(MpscLinkedQueue::new())
+    }
+}
+
+impl <T: Linked> LockFreeBlockingQueue<Box<T>, MpscIntrusiveQueue<T>> {
+
+    /// Create unbounded queue for many producers and one consumer which links
+    /// boxed values through `MpscLink` they embed
+    ///
+    /// Dequeue operations panic when two threads dequeue at the same time
+    pub fn mpsc_intrusive() -> LockFreeBlockingQueue<Box<T>, MpscIntrusiveQueue<T>> {
+        LockFreeBlockingQueue::from_queue(MpscIntrusiveQueue::new())
+    }
+}
+
+impl <T, Q: NonBlockingQueue<T>> LockFreeBlockingQueue<T, Q> {
+
+    /// Create blocking queue on top of specified lock-free queue
+    pub fn from_queue(queue: Q) -> LockFreeBlockingQueue<T, Q> {
+        LockFreeBlockingQueue {
+            queue: queue,
+            mutex: Mutex::new(()),
+            consumers: AtomicUsize::new(0),
+            producers: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            empty: Condvar::new(),
+            full: Condvar::new(),
+            value: PhantomData
+        }
+    }
+
+    /// Wake up consumers if any of them waits, the fence pairs with the one in `wait_for`
+    /// so that either consumer sees pushed value or producer sees waiting consumer
+    fn signal_not_empty(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.consumers.load(Ordering::Relaxed) > 0 {
+            let guard = self.mutex.lock().unwrap();
+            self.empty.notify_all();
+            drop(guard);
+        }
+    }
+
+    fn signal_not_full(&self) {
+        atomic::fence(Ordering::SeqCst);
+        if self.producers.load(Ordering::Relaxed) > 0 {
+            let guard = self.mutex.lock().unwrap();
+            self.full.notify_all();
+            drop(guard);
+        }
+    }
+
+    /// Wait on condvar until ready returns true, queue is closed or deadline is reached
+    /// Return false if deadline is reached
+    fn wait_for<F: Fn() -> bool>(&self, waiters: &AtomicUsize, cond: &Condvar, ready: F, deadline: Option<Instant>) -> bool {
+        let mut guard = self.mutex.lock().unwrap();
+        waiters.fetch_add(1, Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+        let mut in_time = true;
+        while !ready() && !self.is_closed() {
+            match deadline {
+                None => guard = cond.wait(guard).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        in_time = false;
+                        break;
+                    }
+                    guard = cond.wait_timeout(guard, deadline - now).unwrap().0;
+                }
+            }
+        }
+        waiters.fetch_sub(1, Ordering::Relaxed);
+        drop(guard);
+        in_time
+    }
+
+    fn push(&self, mut val: T, deadline: Option<Instant>) -> Result<(), T> {
+        loop {
+            if self.is_closed() {
+                return Err(val);
+            }
+            match self.queue.try_push(val) {
+                Ok(()) => {
+                    self.signal_not_empty();
+                    return Ok(());
+                },
+                Err(rejected) => val = rejected,
+            }
+            if !self.wait_for(&self.producers, &self.full, || !self.queue.is_full(), deadline) {
+                return Err(val);
+            }
+        }
+    }
+
+    fn pop(&self, deadline: Option<Instant>) -> Option<T> {
+        loop {
+            if let Some(val) = self.queue.try_pop() {
+                self.signal_not_full();
+                return Some(val);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            if !self.wait_for(&self.consumers, &self.empty, || !self.queue.is_empty(), deadline) {
+                return None;
+            }
+        }
+    }
+}
+
+impl <T, Q: NonBlockingQueue<T>> BlockingQueue<T> for LockFreeBlockingQueue<T, Q> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Enqueue value into queue
+    /// Could be blocked until dequeue event if queue is full
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        self.push(val, None)
+    }
+
+    /// Dequeue value from queue
+    /// Could be blocked until enqueue event if queue is empty
+    /// Return error if queue is closed and all values were dequeued
+    fn dequeue(&self) -> Result<T, Closed> {
+        self.pop(None).ok_or(Closed)
+    }
+
+    /// Offer value into queue
+    /// If queue is not full and not closed return true otherwise false
+    fn offer(&self, val: T) -> bool {
+        self.try_enqueue(val).is_ok()
+    }
+
+    /// Enqueue value into queue if it is not full and not closed
+    /// otherwise return the value back
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        if self.is_closed() {
+            return Err(val);
+        }
+        let result = self.queue.try_push(val);
+        if result.is_ok() {
+            self.signal_not_empty();
+        }
+        result
+    }
+
+    /// Dequeue value from queue if it is not empty
+    fn poll(&self) -> Option<T> {
+        let result = self.queue.try_pop();
+        if result.is_some() {
+            self.signal_not_full();
+        }
+        result
+    }
+
+    /// Move up to max values from queue into target vector without blocking
+    /// and wake up waiting producers once
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut count = 0;
+        while count < max {
+            match self.queue.try_pop() {
+                Some(val) => target.push(val),
+                None => break,
+            }
+            count += 1;
+        }
+        if count > 0 {
+            self.signal_not_full();
+        }
+        count
+    }
+
+    /// Enqueue all values waking up waiting consumers once while there is free space in queue,
+    /// could be blocked until dequeue event if queue is full
+    /// Return values which were not enqueued if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut iter = iter.into_iter();
+        let mut pushed = false;
+        while let Some(val) = iter.next() {
+            if self.is_closed() {
+                let mut rest = vec![val];
+                rest.extend(iter);
+                return Err(rest);
+            }
+            match self.queue.try_push(val) {
+                Ok(()) => pushed = true,
+                Err(val) => {
+                    // consumers have to be woken up before producer waits for them
+                    if pushed {
+                        self.signal_not_empty();
+                        pushed = false;
+                    }
+                    if let Err(val) = self.push(val, None) {
+                        let mut rest = vec![val];
+                        rest.extend(iter);
+                        return Err(rest);
+                    }
+                },
+            }
+        }
+        if pushed {
+            self.signal_not_empty();
+        }
+        Ok(())
+    }
+
+    /// Offer value into queue waiting up to specified timeout for free space
+    /// Return the value back if queue is still full when timeout elapses
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.push(val, Some(Instant::now() + timeout))
+    }
+
+    /// Dequeue value from queue waiting up to specified timeout for enqueue event
+    /// Return None if queue is still empty when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.pop(Some(Instant::now() + timeout))
+    }
+
+    /// Clone queue head value without removing it from queue
+    fn peek(&self) -> Option<T> where T: Clone {
+        self.queue.peek()
+    }
+
+    /// Close queue, all further enqueues are rejected
+    /// Threads blocked on the queue are woken up, values left in the queue could be dequeued
+    fn close(&self) {
+        let guard = self.mutex.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.empty.notify_all();
+        self.full.notify_all();
+        drop(guard);
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,166 @@
+use std::ptr;
+use std::thread;
+
+use std::marker::PhantomData;
+
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use super::NonBlockingQueue;
+use super::node::Node;
+use super::super::reclaim::{Reclaim, Protect, Epoch};
+
+/// Hazard slots used by queue operations
+const HEAD: usize = 0;
+const NEXT: usize = 1;
+const TAIL: usize = 2;
+
+/// Unbounded lock-free queue of Michael and Scott
+///
+/// Producers link nodes after tail with CAS and consumers move head with CAS,
+/// so they contend only on tail and head pointers. Push is lock-free, pop is lock-free
+/// unless another thread peeks the same value, then consumer waits until the clone is done.
+/// Values are cloned through shared references by several threads, so the queue
+/// could be shared between threads only if values are `Sync`.
+/// Dequeued nodes are destroyed by reclamation scheme `R`, possibly in another thread
+pub struct LockFreeLinkedQueue<T, R = Epoch> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    size: AtomicUsize,
+    reclaim: PhantomData<R>
+}
+
+unsafe impl <T: Send, R> Send for LockFreeLinkedQueue<T, R> { }
+unsafe impl <T: Send + Sync, R> Sync for LockFreeLinkedQueue<T, R> { }
+
+impl <T: Send + 'static, R: Reclaim> LockFreeLinkedQueue<T, R> {
+
+    /// Create empty queue
+    pub fn new() -> LockFreeLinkedQueue<T, R> {
+        let sentinel = Box::into_raw(Box::new(Node::empty()));
+        LockFreeLinkedQueue {
+            head: AtomicPtr::new(sentinel),
+            tail: AtomicPtr::new(sentinel),
+            size: AtomicUsize::new(0),
+            reclaim: PhantomData
+        }
+    }
+}
+
+impl <T: Send + 'static, R: Reclaim> NonBlockingQueue<T> for LockFreeLinkedQueue<T, R> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        let guard = R::guard();
+        let head = guard.protect_ptr(HEAD, &self.head);
+        unsafe { (*head).next.load(Ordering::Acquire).is_null() }
+    }
+
+    /// Unbounded queue is never full
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Link value after the last node, never fails due to unbound capacity
+    fn try_push(&self, val: T) -> Result<(), T> {
+        let node = Box::into_raw(Box::new(Node::non_empty(val)));
+        // size is increased before the node is linked, so that it does not underflow
+        // when consumer takes the value first
+        self.size.fetch_add(1, Ordering::Relaxed);
+        let guard = R::guard();
+        loop {
+            let tail = guard.protect_ptr(TAIL, &self.tail);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+            if tail != self.tail.load(Ordering::Acquire) {
+                continue;
+            }
+            if !next.is_null() {
+                // help producer which linked a node but did not move tail yet
+                self.tail.compare_and_swap(tail, next, Ordering::Release);
+                continue;
+            }
+            if unsafe { (*tail).next.compare_and_swap(ptr::null_mut(), node, Ordering::Release) }.is_null() {
+                self.tail.compare_and_swap(tail, node, Ordering::Release);
+                return Ok(());
+            }
+        }
+    }
+
+    /// Move head to the next node and take its value, the node becomes new sentinel
+    /// Waits for threads which clone the value in peek
+    fn try_pop(&self) -> Option<T> {
+        let guard = R::guard();
+        loop {
+            let head = guard.protect_ptr(HEAD, &self.head);
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = guard.protect_ptr(NEXT, unsafe { &(*head).next });
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            if head == tail {
+                self.tail.compare_and_swap(tail, next, Ordering::Release);
+                continue;
+            }
+            if self.head.compare_and_swap(head, next, Ordering::SeqCst) == head {
+                // only the thread which moved head takes the value of new sentinel,
+                // it waits for threads which clone the value in peek
+                while unsafe { (*next).readers.load(Ordering::SeqCst) } != 0 {
+                    thread::yield_now();
+                }
+                let val = unsafe { (*next).value.take() };
+                self.size.fetch_sub(1, Ordering::Relaxed);
+                unsafe { guard.retire(head); }
+                return val;
+            }
+        }
+    }
+
+    /// Clone head value without removing it from queue
+    ///
+    /// Reader registers on the first node before it checks that head did not move,
+    /// so consumer which moves head waits until the clone is done before it takes the value.
+    /// If head moved the first node is looked up again
+    fn peek(&self) -> Option<T> where T: Clone {
+        let guard = R::guard();
+        loop {
+            let head = guard.protect_ptr(HEAD, &self.head);
+            let next = guard.protect_ptr(NEXT, unsafe { &(*head).next });
+            if head != self.head.load(Ordering::Acquire) {
+                continue;
+            }
+            if next.is_null() {
+                return None;
+            }
+            let node = unsafe { &*next };
+            node.readers.fetch_add(1, Ordering::SeqCst);
+            let val = if head == self.head.load(Ordering::SeqCst) {
+                Some(node.value.clone())
+            }
+            else {
+                None
+            };
+            node.readers.fetch_sub(1, Ordering::Release);
+            if let Some(val) = val {
+                return val;
+            }
+        }
+    }
+}
+
+impl <T, R> Drop for LockFreeLinkedQueue<T, R> {
+
+    fn drop(&mut self) {
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            let current = unsafe { Box::from_raw(node) };
+            node = current.next.load(Ordering::Relaxed);
+        }
+    }
+}
@@ -1,8 +1,52 @@
+use std::time::Duration;
+
+use std::fmt;
+use std::error::Error;
+
 pub use self::array_queue::ArrayBlockingQueue;
 pub use self::linked_queue::UnboundedBlockingQueue;
+pub use self::lock_free_array_queue::LockFreeArrayQueue;
+pub use self::lock_free_linked_queue::LockFreeLinkedQueue;
+pub use self::lock_free_blocking_queue::LockFreeBlockingQueue;
+pub use self::spsc_queue::SpscArrayQueue;
+pub use self::mpsc_queue::{MpscLinkedQueue, MpscIntrusiveQueue, MpscLink, Linked};
+pub use self::priority_queue::{PriorityBlockingQueue, Comparator};
+pub use self::delay_queue::{DelayQueue, Delayed, Delay};
+pub use self::synchronous_queue::SynchronousQueue;
+pub use self::linked_deque::LinkedBlockingDeque;
+pub use self::channel::{channel, Sender, Receiver, Iter};
+pub use self::select::{Select, Selectable, Operation, Signal};
 
 mod array_queue;
 mod linked_queue;
+mod lock_free_array_queue;
+mod lock_free_linked_queue;
+mod lock_free_blocking_queue;
+mod spsc_queue;
+mod mpsc_queue;
+mod priority_queue;
+mod delay_queue;
+mod synchronous_queue;
+mod linked_deque;
+mod node;
+mod channel;
+mod select;
+
+/// Blocking queue for one producer and one consumer, created with `SpscQueue::spsc`
+///
+/// Enqueue and dequeue operations panic when two threads use the same side at the same time
+pub type SpscQueue<T> = LockFreeBlockingQueue<T, SpscArrayQueue<T>>;
+
+/// Unbounded blocking queue for many producers and one consumer, created with `MpscQueue::mpsc`
+///
+/// Dequeue operations panic when two threads dequeue at the same time
+pub type MpscQueue<T> = LockFreeBlockingQueue<T, MpscLinkedQueue<T>>;
+
+/// Unbounded blocking queue for many producers and one consumer which does not allocate,
+/// created with `IntrusiveMpscQueue::mpsc_intrusive`
+///
+/// Dequeue operations panic when two threads dequeue at the same time
+pub type IntrusiveMpscQueue<T> = LockFreeBlockingQueue<Box<T>, MpscIntrusiveQueue<T>>;
 
 pub trait BlockingQueue<T> {
     
@@ -10,11 +54,61 @@ pub trait BlockingQueue<T> {
 
     fn is_empty(&self) -> bool;
 
-    fn enqueue(&self, e: T);
+    fn enqueue(&self, e: T) -> Result<(), T>;
 
-    fn dequeue(&self) -> T;
+    fn dequeue(&self) -> Result<T, Closed>;
 
     fn offer(&self, e: T) -> bool;
 
-    fn peek(&self) -> Option<T>;
-}
\ No newline at end of file
+    fn try_enqueue(&self, e: T) -> Result<(), T>;
+
+    fn poll(&self) -> Option<T>;
+
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize;
+
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> where Self: Sized;
+
+    fn offer_timeout(&self, e: T, timeout: Duration) -> Result<(), T>;
+
+    fn poll_timeout(&self, timeout: Duration) -> Option<T>;
+
+    fn peek(&self) -> Option<T> where T: Clone;
+
+    fn close(&self);
+
+    fn is_closed(&self) -> bool;
+}
+
+/// Queue which never blocks, operations on full or empty queue fail immediately
+pub trait NonBlockingQueue<T> {
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool;
+
+    fn is_full(&self) -> bool;
+
+    fn try_push(&self, e: T) -> Result<(), T>;
+
+    fn try_pop(&self) -> Option<T>;
+
+    fn peek(&self) -> Option<T> where T: Clone;
+}
+
+/// Error returned from dequeue when queue is closed and all its values were dequeued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Closed;
+
+impl fmt::Display for Closed {
+
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "queue is closed")
+    }
+}
+
+impl Error for Closed {
+
+    fn description(&self) -> &str {
+        "queue is closed"
+    }
+}
@@ -0,0 +1,309 @@
+use std::ptr;
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use super::NonBlockingQueue;
+use super::node::Node;
+use super::spsc_queue::Role;
+
+/// Unbounded multi producer single consumer queue of Vyukov
+///
+/// Producer links its node with one swap of tail, so push is wait-free.
+/// Only consumer reads and frees nodes, so no reclamation scheme is needed.
+/// While producer is between the swap and the link consumer does not see
+/// values pushed after it.
+/// Every push allocates a node for the value, values which embed `MpscLink`
+/// could be linked without allocation by `MpscIntrusiveQueue`.
+/// A second thread which pops or peeks at the same time as another one panics
+pub struct MpscLinkedQueue<T> {
+    head: Cell<*mut Node<T>>,
+    consumer: AtomicBool,
+    tail: AtomicPtr<Node<T>>,
+    size: AtomicUsize
+}
+
+unsafe impl <T: Send> Send for MpscLinkedQueue<T> { }
+// head is accessed only by the thread which holds consumer role
+unsafe impl <T: Send> Sync for MpscLinkedQueue<T> { }
+
+impl <T> MpscLinkedQueue<T> {
+
+    /// Create empty queue
+    pub fn new() -> MpscLinkedQueue<T> {
+        let stub = Box::into_raw(Box::new(Node::empty()));
+        MpscLinkedQueue {
+            head: Cell::new(stub),
+            consumer: AtomicBool::new(false),
+            tail: AtomicPtr::new(stub),
+            size: AtomicUsize::new(0)
+        }
+    }
+}
+
+impl <T> NonBlockingQueue<T> for MpscLinkedQueue<T> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unbounded queue is never full
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Link value after the last node, never fails due to unbound capacity
+    fn try_push(&self, val: T) -> Result<(), T> {
+        let node = Box::into_raw(Box::new(Node::non_empty(val)));
+        self.size.fetch_add(1, Ordering::Relaxed);
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+        // consumer frees prev only after it is linked
+        unsafe { (*prev).next.store(node, Ordering::Release); }
+        Ok(())
+    }
+
+    /// Pop value from queue if it is not empty
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn try_pop(&self) -> Option<T> {
+        let role = Role::take(&self.consumer, "MpscLinkedQueue", "consumer");
+        let head = self.head.get();
+        let next = unsafe { (*head).next.load(Ordering::Acquire) };
+        if next.is_null() {
+            return None;
+        }
+        self.head.set(next);
+        let val = unsafe { (*next).value.take() };
+        self.size.fetch_sub(1, Ordering::Relaxed);
+        unsafe { drop(Box::from_raw(head)); }
+        drop(role);
+        val
+    }
+
+    /// Clone head value without removing it from queue, it is an operation of consumer
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn peek(&self) -> Option<T> where T: Clone {
+        let role = Role::take(&self.consumer, "MpscLinkedQueue", "consumer");
+        let next = unsafe { (*self.head.get()).next.load(Ordering::Acquire) };
+        let result = if next.is_null() {
+            None
+        }
+        else {
+            unsafe { (*next).value.clone() }
+        };
+        drop(role);
+        result
+    }
+}
+
+impl <T> Drop for MpscLinkedQueue<T> {
+
+    fn drop(&mut self) {
+        let mut node = self.head.get();
+        while !node.is_null() {
+            let current = unsafe { Box::from_raw(node) };
+            node = current.next.load(Ordering::Relaxed);
+        }
+    }
+}
+
+/// Link which values of `MpscIntrusiveQueue` embed
+#[derive(Debug)]
+pub struct MpscLink {
+    next: AtomicPtr<MpscLink>
+}
+
+impl MpscLink {
+
+    pub fn new() -> MpscLink {
+        MpscLink {
+            next: AtomicPtr::new(ptr::null_mut())
+        }
+    }
+}
+
+impl Clone for MpscLink {
+
+    /// Clone of value is not linked into any queue
+    fn clone(&self) -> MpscLink {
+        MpscLink::new()
+    }
+}
+
+/// Value which could be linked into `MpscIntrusiveQueue`
+///
+/// # Safety
+///
+/// Implementor has to be `#[repr(C)]` struct which first field is `MpscLink`
+/// and `link` has to return reference to that field
+pub unsafe trait Linked {
+
+    fn link(&self) -> &MpscLink;
+}
+
+/// Unbounded intrusive multi producer single consumer queue of Vyukov
+///
+/// Values are linked through `MpscLink` they embed, so queue does not allocate.
+/// Boxed value is owned by the queue until it is popped and the box could be pushed again.
+/// Queue keeps a stub link which is relinked when consumer takes the last value.
+/// A second thread which pops or peeks at the same time as another one panics
+pub struct MpscIntrusiveQueue<T: Linked> {
+    head: Cell<*mut MpscLink>,
+    consumer: AtomicBool,
+    tail: AtomicPtr<MpscLink>,
+    stub: Box<MpscLink>,
+    size: AtomicUsize,
+    values: PhantomData<Box<T>>
+}
+
+unsafe impl <T: Linked + Send> Send for MpscIntrusiveQueue<T> { }
+// head is accessed only by the thread which holds consumer role
+unsafe impl <T: Linked + Send> Sync for MpscIntrusiveQueue<T> { }
+
+impl <T: Linked> MpscIntrusiveQueue<T> {
+
+    /// Create empty queue
+    pub fn new() -> MpscIntrusiveQueue<T> {
+        let stub = Box::new(MpscLink::new());
+        let stub_ptr = &*stub as *const MpscLink as *mut MpscLink;
+        MpscIntrusiveQueue {
+            head: Cell::new(stub_ptr),
+            consumer: AtomicBool::new(false),
+            tail: AtomicPtr::new(stub_ptr),
+            stub: stub,
+            size: AtomicUsize::new(0),
+            values: PhantomData
+        }
+    }
+
+    fn stub(&self) -> *mut MpscLink {
+        &*self.stub as *const MpscLink as *mut MpscLink
+    }
+
+    fn link(&self, link: *mut MpscLink) {
+        unsafe { (*link).next.store(ptr::null_mut(), Ordering::Relaxed); }
+        let prev = self.tail.swap(link, Ordering::AcqRel);
+        unsafe { (*prev).next.store(link, Ordering::Release); }
+    }
+
+    /// Return the first link after stub or None if queue is empty, should be called by consumer
+    fn first(&self) -> *mut MpscLink {
+        let head = self.head.get();
+        if head == self.stub() {
+            unsafe { (*head).next.load(Ordering::Acquire) }
+        }
+        else {
+            head
+        }
+    }
+
+    /// Unlink the first value, should be called by consumer
+    fn unlink(&self) -> Option<Box<T>> {
+        let stub = self.stub();
+        let mut head = self.head.get();
+        let mut next = unsafe { (*head).next.load(Ordering::Acquire) };
+        if head == stub {
+            if next.is_null() {
+                return None;
+            }
+            self.head.set(next);
+            head = next;
+            next = unsafe { (*next).next.load(Ordering::Acquire) };
+        }
+        if next.is_null() {
+            if head != self.tail.load(Ordering::Acquire) {
+                // producer swapped tail but did not link its value yet
+                return None;
+            }
+            // head is the last value, stub is linked after it so that it could be unlinked
+            self.link(stub);
+            next = unsafe { (*head).next.load(Ordering::Acquire) };
+            if next.is_null() {
+                return None;
+            }
+        }
+        self.head.set(next);
+        self.size.fetch_sub(1, Ordering::Relaxed);
+        Some(unsafe { Box::from_raw(head as *mut T) })
+    }
+}
+
+impl <T: Linked> NonBlockingQueue<Box<T>> for MpscIntrusiveQueue<T> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unbounded queue is never full
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Link boxed value after the last one, never fails due to unbound capacity
+    fn try_push(&self, val: Box<T>) -> Result<(), Box<T>> {
+        let link = Box::into_raw(val);
+        debug_assert!(unsafe { (*link).link() as *const MpscLink as usize } == link as usize,
+            "MpscLink should be the first field of value");
+        self.size.fetch_add(1, Ordering::Relaxed);
+        self.link(link as *mut MpscLink);
+        Ok(())
+    }
+
+    /// Pop value from queue if it is not empty
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn try_pop(&self) -> Option<Box<T>> {
+        let role = Role::take(&self.consumer, "MpscIntrusiveQueue", "consumer");
+        let val = self.unlink();
+        drop(role);
+        val
+    }
+
+    /// Clone the first value without removing it from queue, it is an operation of consumer
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn peek(&self) -> Option<Box<T>> where Box<T>: Clone {
+        let role = Role::take(&self.consumer, "MpscIntrusiveQueue", "consumer");
+        let first = self.first() as *mut T;
+        let result = if first.is_null() {
+            None
+        }
+        else {
+            // box has the layout of pointer, value is only borrowed and stays owned by queue
+            let borrowed = unsafe { &*(&first as *const *mut T as *const Box<T>) };
+            Some(borrowed.clone())
+        };
+        drop(role);
+        result
+    }
+}
+
+impl <T: Linked> Drop for MpscIntrusiveQueue<T> {
+
+    fn drop(&mut self) {
+        while self.unlink().is_some() { }
+    }
+}
@@ -0,0 +1,113 @@
+use std::ptr;
+
+use std::boxed::Box;
+
+use std::option::Option;
+
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+use std::clone::Clone;
+use std::marker::Copy;
+
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// Node of linked queues, `next` is atomic so that it could be read
+/// while another thread links a new node
+///
+/// `readers` counts threads which clone the value of a node linked in a lock-free queue,
+/// the value could be taken out only when there are no readers
+pub struct Node<T> {
+    pub value: Option<T>,
+    pub next: AtomicPtr<Node<T>>,
+    pub readers: AtomicUsize
+}
+
+impl <T> Node<T> {
+
+    pub fn empty() -> Node<T> {
+        Node {
+            value: None,
+            next: AtomicPtr::new(ptr::null_mut()),
+            readers: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn non_empty(value: T) -> Node<T> {
+        Node {
+            value: Some(value),
+            next: AtomicPtr::new(ptr::null_mut()),
+            readers: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn next(&self) -> Option<Link<T>> {
+        let next = self.next.load(Ordering::Acquire);
+        if next.is_null() {
+            None
+        }
+        else {
+            Some(Link { ptr: next })
+        }
+    }
+
+    pub fn set_next(&self, link: Link<T>) {
+        self.next.store(link.ptr, Ordering::Release);
+    }
+}
+
+pub struct Link<T> {
+    pub ptr: *mut Node<T>
+}
+
+impl <T> Link<T> {
+
+    pub fn new(node: Node<T>) -> Link<T> {
+        Link {
+            ptr: Box::into_raw(Box::new(node))
+        }
+    }
+}
+
+impl <T> Deref for Link<T> {
+    type Target = Node<T>;
+
+    fn deref(&self) -> &Node<T> {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl <T> DerefMut for Link<T> {
+
+    fn deref_mut(&mut self) -> &mut Node<T> {
+        unsafe { &mut *self.ptr }
+    }
+}
+
+impl<T> Clone for Link<T> {
+
+    fn clone(&self) -> Link<T> {
+        Link { ptr: self.ptr }
+    }
+}
+
+impl <T> Copy for Link<T> { }
+unsafe impl <T: Send> Send for Link<T> { }
+
+/// Node of linked deques, links are read and written only under the lock of deque
+pub struct DequeNode<T> {
+    pub value: T,
+    pub prev: *mut DequeNode<T>,
+    pub next: *mut DequeNode<T>
+}
+
+impl <T> DequeNode<T> {
+
+    pub fn boxed(value: T) -> *mut DequeNode<T> {
+        Box::into_raw(Box::new(DequeNode {
+            value: value,
+            prev: ptr::null_mut(),
+            next: ptr::null_mut()
+        }))
+    }
+}
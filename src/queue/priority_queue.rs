@@ -0,0 +1,312 @@
+use std::cmp;
+use std::cmp::Ordering as CmpOrdering;
+use std::sync::{Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{BlockingQueue, Closed};
+
+/// Comparator which orders values of queue, greater value has higher priority
+pub type Comparator<T> = Box<Fn(&T, &T) -> CmpOrdering + Send + Sync>;
+
+/// Blocking queue which dequeues value with the highest priority first
+///
+/// Values are kept in binary heap ordered by natural order of values or by comparator.
+/// Queue is unbounded unless it is created with capacity, in that case enqueue
+/// waits for free space. Current implementation is based on one Mutex and two Condvars
+pub struct PriorityBlockingQueue<T> {
+    heap: Mutex<Vec<T>>,
+    compare: Comparator<T>,
+    capacity: Option<usize>,
+    closed: AtomicBool,
+    empty: Condvar,
+    full: Condvar
+}
+
+impl <T: Ord> PriorityBlockingQueue<T> {
+
+    /// Create unbounded queue ordered by natural order of values
+    pub fn new() -> PriorityBlockingQueue<T> {
+        PriorityBlockingQueue::create(None, Box::new(|a: &T, b: &T| a.cmp(b)))
+    }
+
+    /// Create bounded queue ordered by natural order of values
+    pub fn with_capacity(capacity: usize) -> PriorityBlockingQueue<T> {
+        PriorityBlockingQueue::create(Some(capacity), Box::new(|a: &T, b: &T| a.cmp(b)))
+    }
+}
+
+impl <T> PriorityBlockingQueue<T> {
+
+    /// Create unbounded queue ordered by comparator
+    pub fn with_comparator<F>(compare: F) -> PriorityBlockingQueue<T>
+            where F: Fn(&T, &T) -> CmpOrdering + Send + Sync + 'static {
+        PriorityBlockingQueue::create(None, Box::new(compare))
+    }
+
+    /// Create bounded queue ordered by comparator
+    pub fn with_capacity_and_comparator<F>(capacity: usize, compare: F) -> PriorityBlockingQueue<T>
+            where F: Fn(&T, &T) -> CmpOrdering + Send + Sync + 'static {
+        PriorityBlockingQueue::create(Some(capacity), Box::new(compare))
+    }
+
+    fn create(capacity: Option<usize>, compare: Comparator<T>) -> PriorityBlockingQueue<T> {
+        assert!(capacity != Some(0), "capacity of bounded queue should be greater than zero");
+        PriorityBlockingQueue {
+            heap: Mutex::new(Vec::with_capacity(capacity.unwrap_or(0))),
+            compare: compare,
+            capacity: capacity,
+            closed: AtomicBool::new(false),
+            empty: Condvar::new(),
+            full: Condvar::new()
+        }
+    }
+
+    /// Return capacity of bounded queue or None if queue is unbounded
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    fn is_full(&self, heap: &[T]) -> bool {
+        match self.capacity {
+            Some(capacity) => heap.len() >= capacity,
+            None => false,
+        }
+    }
+
+    /// Wait while queue is full and not closed, return None if queue is closed
+    /// or if deadline is reached
+    fn wait_not_full<'a>(&self, mut heap: MutexGuard<'a, Vec<T>>, deadline: Option<Instant>) -> Option<MutexGuard<'a, Vec<T>>> {
+        while self.is_full(&heap) && !self.is_closed() {
+            match deadline {
+                None => heap = self.full.wait(heap).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    heap = self.full.wait_timeout(heap, deadline - now).unwrap().0;
+                }
+            }
+        }
+        if self.is_closed() {
+            return None;
+        }
+        Some(heap)
+    }
+
+    /// Wait while queue is empty and not closed, return None if queue is closed and empty
+    /// or if deadline is reached
+    fn wait_not_empty<'a>(&self, mut heap: MutexGuard<'a, Vec<T>>, deadline: Option<Instant>) -> Option<MutexGuard<'a, Vec<T>>> {
+        while heap.is_empty() && !self.is_closed() {
+            match deadline {
+                None => heap = self.empty.wait(heap).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    heap = self.empty.wait_timeout(heap, deadline - now).unwrap().0;
+                }
+            }
+        }
+        if heap.is_empty() {
+            return None;
+        }
+        Some(heap)
+    }
+
+    fn push(&self, heap: &mut Vec<T>, val: T) {
+        heap.push(val);
+        let mut index = heap.len() - 1;
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if (self.compare)(&heap[index], &heap[parent]) != CmpOrdering::Greater {
+                break;
+            }
+            heap.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Remove value with the highest priority, should be called when heap is not empty
+    fn pop(&self, heap: &mut Vec<T>) -> T {
+        let val = heap.swap_remove(0);
+        let len = heap.len();
+        let mut index = 0;
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut greatest = index;
+            if left < len && (self.compare)(&heap[left], &heap[greatest]) == CmpOrdering::Greater {
+                greatest = left;
+            }
+            if right < len && (self.compare)(&heap[right], &heap[greatest]) == CmpOrdering::Greater {
+                greatest = right;
+            }
+            if greatest == index {
+                break;
+            }
+            heap.swap(index, greatest);
+            index = greatest;
+        }
+        val
+    }
+
+    fn enqueue_before(&self, val: T, deadline: Option<Instant>) -> Result<(), T> {
+        let heap = self.heap.lock().unwrap();
+        let mut heap = match self.wait_not_full(heap, deadline) {
+            Some(heap) => heap,
+            None => return Err(val),
+        };
+        self.push(&mut heap, val);
+        self.empty.notify_one();
+        drop(heap);
+        Ok(())
+    }
+
+    fn dequeue_before(&self, deadline: Option<Instant>) -> Option<T> {
+        let heap = self.heap.lock().unwrap();
+        let mut heap = match self.wait_not_empty(heap, deadline) {
+            Some(heap) => heap,
+            None => return None,
+        };
+        let val = self.pop(&mut heap);
+        self.full.notify_one();
+        drop(heap);
+        Some(val)
+    }
+}
+
+impl <T> BlockingQueue<T> for PriorityBlockingQueue<T> {
+
+    /// Return size of current queue
+    fn len(&self) -> usize {
+        self.heap.lock().unwrap().len()
+    }
+
+    /// Check if current queue is empty
+    fn is_empty(&self) -> bool {
+        self.heap.lock().unwrap().is_empty()
+    }
+
+    /// Enqueue value into queue
+    /// Could be blocked until dequeue event if bounded queue is full
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        self.enqueue_before(val, None)
+    }
+
+    /// Dequeue value with the highest priority
+    /// Could be blocked until enqueue event if queue is empty
+    /// Return error if queue is closed and all values were dequeued
+    fn dequeue(&self) -> Result<T, Closed> {
+        self.dequeue_before(None).ok_or(Closed)
+    }
+
+    /// Offer value into queue
+    /// If queue is not full and not closed return true otherwise false
+    fn offer(&self, val: T) -> bool {
+        self.try_enqueue(val).is_ok()
+    }
+
+    /// Enqueue value into queue if it is not full and not closed
+    /// otherwise return the value back
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        let mut heap = self.heap.lock().unwrap();
+        if self.is_full(&heap) || self.is_closed() {
+            return Err(val);
+        }
+        self.push(&mut heap, val);
+        self.empty.notify_one();
+        drop(heap);
+        Ok(())
+    }
+
+    /// Dequeue value with the highest priority if queue is not empty
+    fn poll(&self) -> Option<T> {
+        let mut heap = self.heap.lock().unwrap();
+        if heap.is_empty() {
+            return None;
+        }
+        let val = self.pop(&mut heap);
+        self.full.notify_one();
+        drop(heap);
+        Some(val)
+    }
+
+    /// Move up to max values from queue into target vector in priority order without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut heap = self.heap.lock().unwrap();
+        let count = cmp::min(heap.len(), max);
+        target.reserve(count);
+        for _ in 0..count {
+            let val = self.pop(&mut heap);
+            target.push(val);
+        }
+        if count > 0 {
+            self.full.notify_all();
+        }
+        drop(heap);
+        count
+    }
+
+    /// Enqueue all values holding the lock and notifying waiting threads once
+    /// while there is free space in queue, could be blocked until dequeue event if bounded queue is full
+    /// Return values which were not enqueued if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut iter = iter.into_iter().peekable();
+        let mut heap = self.heap.lock().unwrap();
+        while iter.peek().is_some() {
+            heap = match self.wait_not_full(heap, None) {
+                Some(heap) => heap,
+                None => return Err(iter.collect()),
+            };
+            while !self.is_full(&heap) {
+                match iter.next() {
+                    Some(val) => self.push(&mut heap, val),
+                    None => break,
+                }
+            }
+            self.empty.notify_all();
+        }
+        drop(heap);
+        Ok(())
+    }
+
+    /// Offer value into queue waiting up to specified timeout for free space
+    /// Return the value back if queue is still full when timeout elapses
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.enqueue_before(val, Some(Instant::now() + timeout))
+    }
+
+    /// Dequeue value with the highest priority waiting up to specified timeout for enqueue event
+    /// Return None if queue is still empty when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.dequeue_before(Some(Instant::now() + timeout))
+    }
+
+    /// Clone value with the highest priority without removing it from queue
+    fn peek(&self) -> Option<T> where T: Clone {
+        let heap = self.heap.lock().unwrap();
+        let result = heap.first().cloned();
+        drop(heap);
+        result
+    }
+
+    /// Close queue, all further enqueues are rejected
+    /// Threads blocked on the queue are woken up, values left in the queue could be dequeued
+    fn close(&self) {
+        let heap = self.heap.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        self.empty.notify_all();
+        self.full.notify_all();
+        drop(heap);
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
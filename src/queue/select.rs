@@ -0,0 +1,217 @@
+use std::sync::{Arc, Mutex, Condvar};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Operation which `Select` waits to become ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Send,
+    Recv
+}
+
+/// Queue handle which could be waited on by `Select`
+pub trait Selectable {
+
+    /// Check if operation would not block, operation on closed queue never blocks
+    fn is_ready(&self, op: Operation) -> bool;
+
+    /// Register signal which is notified on every change of the queue
+    fn watch(&self, signal: &Arc<Signal>);
+
+    /// Remove signal registered with `watch`
+    fn unwatch(&self, signal: &Arc<Signal>);
+}
+
+/// Wakes up thread which waits in `Select`
+pub struct Signal {
+    ready: Mutex<bool>,
+    cond: Condvar
+}
+
+impl Signal {
+
+    fn new() -> Signal {
+        Signal {
+            ready: Mutex::new(false),
+            cond: Condvar::new()
+        }
+    }
+
+    fn notify(&self) {
+        let mut ready = self.ready.lock().unwrap();
+        *ready = true;
+        self.cond.notify_one();
+        drop(ready);
+    }
+
+    fn reset(&self) {
+        let mut ready = self.ready.lock().unwrap();
+        *ready = false;
+        drop(ready);
+    }
+
+    /// Wait until signal is notified, return false if deadline is reached before
+    fn wait(&self, deadline: Option<Instant>) -> bool {
+        let mut ready = self.ready.lock().unwrap();
+        while !*ready {
+            match deadline {
+                None => ready = self.cond.wait(ready).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return false;
+                    }
+                    ready = self.cond.wait_timeout(ready, deadline - now).unwrap().0;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Signals registered on a queue
+///
+/// Signals should be added, removed and notified under the lock of the queue
+/// so that a change of the queue could not be missed between registration and check
+pub struct Observers {
+    signals: Mutex<Vec<Arc<Signal>>>,
+    count: AtomicUsize
+}
+
+impl Observers {
+
+    pub fn new() -> Observers {
+        Observers {
+            signals: Mutex::new(Vec::new()),
+            count: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn add(&self, signal: &Arc<Signal>) {
+        let mut signals = self.signals.lock().unwrap();
+        signals.push(signal.clone());
+        self.count.store(signals.len(), Ordering::Relaxed);
+    }
+
+    pub fn remove(&self, signal: &Arc<Signal>) {
+        let mut signals = self.signals.lock().unwrap();
+        signals.retain(|s| &**s as *const Signal != &**signal as *const Signal);
+        self.count.store(signals.len(), Ordering::Relaxed);
+    }
+
+    pub fn notify(&self) {
+        if self.count.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let signals = self.signals.lock().unwrap();
+        for signal in signals.iter() {
+            signal.notify();
+        }
+    }
+}
+
+/// Wait until one of several queues is ready to send or receive
+///
+/// Readiness only means that operation would not block at the moment of check,
+/// another thread could take the value or free space first, so the selected
+/// queue should be used with `poll` or `try_enqueue`
+///
+/// ```ignore
+/// let mut select = Select::new();
+/// let first = select.recv(&first_queue);
+/// let second = select.recv(&second_queue);
+/// let index = select.ready();
+/// if index == first { first_queue.poll(); } else { second_queue.poll(); }
+/// ```
+pub struct Select<'a> {
+    cases: Vec<(&'a Selectable, Operation)>,
+    next: usize
+}
+
+impl <'a> Select<'a> {
+
+    /// Create select without cases
+    pub fn new() -> Select<'a> {
+        Select {
+            cases: Vec::new(),
+            next: 0
+        }
+    }
+
+    /// Add case which is ready when value could be received from queue
+    /// Return index of the case
+    pub fn recv(&mut self, queue: &'a Selectable) -> usize {
+        self.cases.push((queue, Operation::Recv));
+        self.cases.len() - 1
+    }
+
+    /// Add case which is ready when value could be sent into queue
+    /// Return index of the case
+    pub fn send(&mut self, queue: &'a Selectable) -> usize {
+        self.cases.push((queue, Operation::Send));
+        self.cases.len() - 1
+    }
+
+    /// Return index of ready case without blocking
+    pub fn try_ready(&mut self) -> Option<usize> {
+        self.find_ready()
+    }
+
+    /// Block until one of cases is ready and return its index
+    ///
+    /// # Panics
+    ///
+    /// Panics if select has no cases
+    pub fn ready(&mut self) -> usize {
+        assert!(!self.cases.is_empty(), "select without cases would block forever");
+        self.wait(None).unwrap()
+    }
+
+    /// Block until one of cases is ready or timeout elapses
+    /// Return None if no case is ready when timeout elapses
+    pub fn ready_timeout(&mut self, timeout: Duration) -> Option<usize> {
+        self.wait(Some(Instant::now() + timeout))
+    }
+
+    fn wait(&mut self, deadline: Option<Instant>) -> Option<usize> {
+        if let Some(index) = self.find_ready() {
+            return Some(index);
+        }
+        let signal = Arc::new(Signal::new());
+        for &(queue, _) in &self.cases {
+            queue.watch(&signal);
+        }
+        let mut result = None;
+        loop {
+            // signal is reset before the check, so a change that happens after it
+            // wakes up the wait below
+            signal.reset();
+            if let Some(index) = self.find_ready() {
+                result = Some(index);
+                break;
+            }
+            if !signal.wait(deadline) {
+                break;
+            }
+        }
+        for &(queue, _) in &self.cases {
+            queue.unwatch(&signal);
+        }
+        result
+    }
+
+    /// Check cases starting from the one after last selected,
+    /// so that one always ready queue does not starve others
+    fn find_ready(&mut self) -> Option<usize> {
+        let len = self.cases.len();
+        for i in 0..len {
+            let index = (self.next + i) % len;
+            let (queue, op) = self.cases[index];
+            if queue.is_ready(op) {
+                self.next = (index + 1) % len;
+                return Some(index);
+            }
+        }
+        None
+    }
+}
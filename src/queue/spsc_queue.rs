@@ -0,0 +1,178 @@
+extern crate alloc;
+
+use self::alloc::raw_vec::RawVec;
+use std::ptr;
+use std::cmp;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use super::NonBlockingQueue;
+use super::super::round_up_to_next_highest_power_of_two;
+
+const MIN_CAPACITY: usize = 2;
+
+const CACHE_LINE: usize = 64;
+
+/// Exclusive right to act as producer or consumer of a single producer or single consumer queue
+///
+/// Taking the role is one uncontended swap when the queue is used as intended,
+/// a second thread which tries to take the role at the same time panics
+pub struct Role<'a> {
+    flag: &'a AtomicBool
+}
+
+impl <'a> Role<'a> {
+
+    pub fn take(flag: &'a AtomicBool, queue: &str, role: &str) -> Role<'a> {
+        if flag.swap(true, Ordering::Acquire) {
+            panic!("{} is used by more than one {} at the same time", queue, role);
+        }
+        Role {
+            flag: flag
+        }
+    }
+}
+
+impl <'a> Drop for Role<'a> {
+
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+/// Bounded wait-free single producer single consumer queue based on ring buffer
+///
+/// Producer and consumer keep their indices on separate cache lines together
+/// with cached copy of the other index, so they read index of each other
+/// only when the cached copy says that queue is full or empty.
+/// Queue is shared by `&self`, a second thread which pushes or pops
+/// at the same time as another one panics
+#[repr(C)]
+pub struct SpscArrayQueue<T> {
+    _pad0: [u8; CACHE_LINE],
+    head: AtomicUsize,
+    cached_tail: Cell<usize>,
+    consumer: AtomicBool,
+    _pad1: [u8; CACHE_LINE],
+    tail: AtomicUsize,
+    cached_head: Cell<usize>,
+    producer: AtomicBool,
+    _pad2: [u8; CACHE_LINE],
+    mask: usize,
+    data: RawVec<T>
+}
+
+unsafe impl <T: Send> Send for SpscArrayQueue<T> { }
+// cached indices are accessed only by the thread which holds producer or consumer role
+unsafe impl <T: Send> Sync for SpscArrayQueue<T> { }
+
+impl <T> SpscArrayQueue<T> {
+
+    /// Create queue with capacity rounded up to the next power of two
+    pub fn with_capacity(capacity: usize) -> SpscArrayQueue<T> {
+        let capacity = round_up_to_next_highest_power_of_two(cmp::max(capacity, MIN_CAPACITY));
+        SpscArrayQueue {
+            _pad0: [0; CACHE_LINE],
+            head: AtomicUsize::new(0),
+            cached_tail: Cell::new(0),
+            consumer: AtomicBool::new(false),
+            _pad1: [0; CACHE_LINE],
+            tail: AtomicUsize::new(0),
+            cached_head: Cell::new(0),
+            producer: AtomicBool::new(false),
+            _pad2: [0; CACHE_LINE],
+            mask: capacity - 1,
+            data: RawVec::with_capacity(capacity)
+        }
+    }
+
+    /// Return capacity of current queue
+    pub fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+}
+
+impl <T> NonBlockingQueue<T> for SpscArrayQueue<T> {
+
+    /// Return number of values in queue, it could be outdated when it is returned
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    /// Check if queue is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if queue is full
+    fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Push value into queue if it is not full otherwise return the value back
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pushes into the queue at the same time
+    fn try_push(&self, val: T) -> Result<(), T> {
+        let role = Role::take(&self.producer, "SpscArrayQueue", "producer");
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.cached_head.get()) == self.capacity() {
+            self.cached_head.set(self.head.load(Ordering::Acquire));
+            if tail.wrapping_sub(self.cached_head.get()) == self.capacity() {
+                return Err(val);
+            }
+        }
+        unsafe { ptr::write(self.data.ptr().offset((tail & self.mask) as isize), val); }
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        drop(role);
+        Ok(())
+    }
+
+    /// Pop value from queue if it is not empty
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn try_pop(&self) -> Option<T> {
+        let role = Role::take(&self.consumer, "SpscArrayQueue", "consumer");
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.cached_tail.get() {
+            self.cached_tail.set(self.tail.load(Ordering::Acquire));
+            if head == self.cached_tail.get() {
+                return None;
+            }
+        }
+        let val = unsafe { ptr::read(self.data.ptr().offset((head & self.mask) as isize)) };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        drop(role);
+        Some(val)
+    }
+
+    /// Clone head value without removing it from queue, it is an operation of consumer
+    ///
+    /// # Panics
+    ///
+    /// Panics if another thread pops from the queue at the same time
+    fn peek(&self) -> Option<T> where T: Clone {
+        let role = Role::take(&self.consumer, "SpscArrayQueue", "consumer");
+        let head = self.head.load(Ordering::Relaxed);
+        let result = if head == self.tail.load(Ordering::Acquire) {
+            None
+        }
+        else {
+            unsafe { Some((*self.data.ptr().offset((head & self.mask) as isize)).clone()) }
+        };
+        drop(role);
+        result
+    }
+}
+
+impl <T> Drop for SpscArrayQueue<T> {
+
+    fn drop(&mut self) {
+        while self.try_pop().is_some() { }
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, Condvar};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{BlockingQueue, Closed};
+
+/// How long operation waits for a matching thread
+#[derive(Clone, Copy)]
+enum Timeout {
+    Now,
+    At(Instant),
+    Never
+}
+
+impl Timeout {
+
+    fn after(timeout: Duration) -> Timeout {
+        Timeout::At(Instant::now() + timeout)
+    }
+
+    fn is_reached(&self) -> bool {
+        match *self {
+            Timeout::Now => true,
+            Timeout::At(deadline) => Instant::now() >= deadline,
+            Timeout::Never => false,
+        }
+    }
+}
+
+/// Thread waiting for a match, producer waits with its value in the slot
+/// and consumer waits for a value to be put into the slot
+struct Waiter<T> {
+    slot: Mutex<Option<T>>,
+    cond: Condvar
+}
+
+impl <T> Waiter<T> {
+
+    fn new(slot: Option<T>) -> Waiter<T> {
+        Waiter {
+            slot: Mutex::new(slot),
+            cond: Condvar::new()
+        }
+    }
+}
+
+struct State<T> {
+    producers: VecDeque<Arc<Waiter<T>>>,
+    consumers: VecDeque<Arc<Waiter<T>>>
+}
+
+/// Blocking queue without capacity, every enqueue waits for a matching dequeue and vice versa
+///
+/// In fair mode waiting threads are matched in FIFO order, in unfair mode the most recent
+/// waiting thread is matched first which keeps it hot in cache but could starve others
+pub struct SynchronousQueue<T> {
+    state: Mutex<State<T>>,
+    fair: bool,
+    closed: AtomicBool
+}
+
+impl <T> SynchronousQueue<T> {
+
+    /// Create queue in unfair mode
+    pub fn new() -> SynchronousQueue<T> {
+        SynchronousQueue::with_fairness(false)
+    }
+
+    /// Create queue in fair mode
+    pub fn fair() -> SynchronousQueue<T> {
+        SynchronousQueue::with_fairness(true)
+    }
+
+    fn with_fairness(fair: bool) -> SynchronousQueue<T> {
+        SynchronousQueue {
+            state: Mutex::new(State {
+                producers: VecDeque::new(),
+                consumers: VecDeque::new()
+            }),
+            fair: fair,
+            closed: AtomicBool::new(false)
+        }
+    }
+
+    /// Check if waiting threads are matched in FIFO order
+    pub fn is_fair(&self) -> bool {
+        self.fair
+    }
+
+    fn next_waiter(&self, waiters: &mut VecDeque<Arc<Waiter<T>>>) -> Option<Arc<Waiter<T>>> {
+        if self.fair {
+            waiters.pop_front()
+        }
+        else {
+            waiters.pop_back()
+        }
+    }
+
+    /// Wait on the condvar of waiter until it is notified or deadline is reached
+    fn wait<'a>(&self, state: MutexGuard<'a, State<T>>, waiter: &Waiter<T>, timeout: Timeout) -> MutexGuard<'a, State<T>> {
+        match timeout {
+            Timeout::At(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return state;
+                }
+                waiter.cond.wait_timeout(state, deadline - now).unwrap().0
+            },
+            _ => waiter.cond.wait(state).unwrap(),
+        }
+    }
+
+    /// Hand value to a waiting consumer or wait until a consumer takes it
+    fn transfer(&self, val: T, timeout: Timeout) -> Result<(), T> {
+        let mut state = self.state.lock().unwrap();
+        if self.is_closed() {
+            return Err(val);
+        }
+        if let Some(consumer) = self.next_waiter(&mut state.consumers) {
+            *consumer.slot.lock().unwrap() = Some(val);
+            consumer.cond.notify_one();
+            return Ok(());
+        }
+        if let Timeout::Now = timeout {
+            return Err(val);
+        }
+        let waiter = Arc::new(Waiter::new(Some(val)));
+        state.producers.push_back(waiter.clone());
+        loop {
+            let taken = waiter.slot.lock().unwrap().is_none();
+            if taken {
+                return Ok(());
+            }
+            if self.is_closed() || timeout.is_reached() {
+                state.producers.retain(|w| &**w as *const Waiter<T> != &*waiter as *const Waiter<T>);
+                let val = waiter.slot.lock().unwrap().take().unwrap();
+                drop(state);
+                return Err(val);
+            }
+            state = self.wait(state, &waiter, timeout);
+        }
+    }
+
+    /// Take value from a waiting producer or wait until a producer hands one
+    fn take(&self, timeout: Timeout) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(producer) = self.next_waiter(&mut state.producers) {
+            let val = producer.slot.lock().unwrap().take();
+            producer.cond.notify_one();
+            return val;
+        }
+        if self.is_closed() {
+            return None;
+        }
+        if let Timeout::Now = timeout {
+            return None;
+        }
+        let waiter = Arc::new(Waiter::new(None));
+        state.consumers.push_back(waiter.clone());
+        loop {
+            let handed = waiter.slot.lock().unwrap().take();
+            if handed.is_some() {
+                return handed;
+            }
+            if self.is_closed() || timeout.is_reached() {
+                state.consumers.retain(|w| &**w as *const Waiter<T> != &*waiter as *const Waiter<T>);
+                drop(state);
+                return None;
+            }
+            state = self.wait(state, &waiter, timeout);
+        }
+    }
+}
+
+impl <T> BlockingQueue<T> for SynchronousQueue<T> {
+
+    /// Queue has no capacity, so it always has zero size
+    fn len(&self) -> usize {
+        0
+    }
+
+    /// Queue has no capacity, so it is always empty
+    fn is_empty(&self) -> bool {
+        true
+    }
+
+    /// Hand value to a consumer
+    /// Could be blocked until a consumer takes the value
+    /// Return the value back if queue is closed
+    fn enqueue(&self, val: T) -> Result<(), T> {
+        self.transfer(val, Timeout::Never)
+    }
+
+    /// Take value from a producer
+    /// Could be blocked until a producer hands a value
+    /// Return error if queue is closed
+    fn dequeue(&self) -> Result<T, Closed> {
+        self.take(Timeout::Never).ok_or(Closed)
+    }
+
+    /// Hand value to a consumer if one is waiting, return true if value was taken
+    fn offer(&self, val: T) -> bool {
+        self.try_enqueue(val).is_ok()
+    }
+
+    /// Hand value to a consumer if one is waiting otherwise return the value back
+    fn try_enqueue(&self, val: T) -> Result<(), T> {
+        self.transfer(val, Timeout::Now)
+    }
+
+    /// Take value from a producer if one is waiting
+    fn poll(&self) -> Option<T> {
+        self.take(Timeout::Now)
+    }
+
+    /// Take values from up to max waiting producers without blocking
+    /// Return number of moved values
+    fn drain_to(&self, target: &mut Vec<T>, max: usize) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let mut count = 0;
+        while count < max {
+            match self.next_waiter(&mut state.producers) {
+                Some(producer) => {
+                    if let Some(val) = producer.slot.lock().unwrap().take() {
+                        target.push(val);
+                    }
+                    producer.cond.notify_one();
+                },
+                None => break,
+            }
+            count += 1;
+        }
+        drop(state);
+        count
+    }
+
+    /// Hand values one by one, every value waits for its own consumer
+    /// Return values which were not taken if queue is closed
+    fn enqueue_all<I: IntoIterator<Item = T>>(&self, iter: I) -> Result<(), Vec<T>> {
+        let mut iter = iter.into_iter();
+        while let Some(val) = iter.next() {
+            if let Err(val) = self.enqueue(val) {
+                let mut rest = vec![val];
+                rest.extend(iter);
+                return Err(rest);
+            }
+        }
+        Ok(())
+    }
+
+    /// Hand value to a consumer waiting up to specified timeout for it
+    /// Return the value back if no consumer takes it when timeout elapses
+    fn offer_timeout(&self, val: T, timeout: Duration) -> Result<(), T> {
+        self.transfer(val, Timeout::after(timeout))
+    }
+
+    /// Take value from a producer waiting up to specified timeout for it
+    /// Return None if no producer hands a value when timeout elapses
+    fn poll_timeout(&self, timeout: Duration) -> Option<T> {
+        self.take(Timeout::after(timeout))
+    }
+
+    /// Queue does not hold values, so there is nothing to peek
+    fn peek(&self) -> Option<T> {
+        None
+    }
+
+    /// Close queue, all further enqueues are rejected
+    /// Waiting producers get their values back and waiting consumers get error
+    fn close(&self) {
+        let state = self.state.lock().unwrap();
+        self.closed.store(true, Ordering::Relaxed);
+        for waiter in state.producers.iter().chain(state.consumers.iter()) {
+            waiter.cond.notify_one();
+        }
+        drop(state);
+    }
+
+    /// Check if queue is closed
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
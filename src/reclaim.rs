@@ -0,0 +1,100 @@
+//! Memory reclamation schemes which lock-free structures could be parameterized with
+//!
+//! `Epoch` is cheaper for readers but a thread that stays pinned for a long time
+//! stops reclamation of all garbage. `Hazards` protects every pointer separately,
+//! so amount of garbage stays bounded whatever threads do
+
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use super::epoch;
+use super::hazard;
+
+/// Reclamation scheme that provides guards for operations on a structure
+pub trait Reclaim {
+    type Guard: Protect;
+
+    /// Return guard which protects pointers loaded during one operation
+    fn guard() -> Self::Guard;
+}
+
+/// Guard of an operation on a lock-free structure
+///
+/// An operation uses at most `hazard::SLOTS` slots. Pointer returned from `protect`
+/// stays valid until the same slot protects another pointer or the guard is dropped
+pub trait Protect {
+
+    /// Load link which could hold a mark in its lowest bit and protect the node it points to
+    fn protect(&self, slot: usize, link: &AtomicUsize) -> usize;
+
+    /// Load pointer and protect the object it points to
+    fn protect_ptr<T>(&self, slot: usize, link: &AtomicPtr<T>) -> *mut T;
+
+    /// Protect pointer that is already protected by another slot
+    fn copy(&self, slot: usize, ptr: usize);
+
+    /// Destroy object once no thread could access it
+    ///
+    /// # Safety
+    ///
+    /// The pointer has to be obtained from `Box::into_raw` and to be unreachable
+    /// for operations which start after this call
+    unsafe fn retire<T: Send + 'static>(&self, ptr: *mut T);
+}
+
+/// Epoch based reclamation, see `concrust::epoch`
+pub struct Epoch;
+
+impl Reclaim for Epoch {
+    type Guard = epoch::Guard;
+
+    fn guard() -> epoch::Guard {
+        epoch::pin()
+    }
+}
+
+impl Protect for epoch::Guard {
+
+    fn protect(&self, _slot: usize, link: &AtomicUsize) -> usize {
+        link.load(Ordering::Acquire)
+    }
+
+    fn protect_ptr<T>(&self, _slot: usize, link: &AtomicPtr<T>) -> *mut T {
+        link.load(Ordering::Acquire)
+    }
+
+    fn copy(&self, _slot: usize, _ptr: usize) { }
+
+    unsafe fn retire<T: Send + 'static>(&self, ptr: *mut T) {
+        self.defer_destroy(ptr)
+    }
+}
+
+/// Hazard pointer reclamation, see `concrust::hazard`
+pub struct Hazards;
+
+impl Reclaim for Hazards {
+    type Guard = hazard::Guard;
+
+    fn guard() -> hazard::Guard {
+        hazard::guard()
+    }
+}
+
+impl Protect for hazard::Guard {
+
+    fn protect(&self, slot: usize, link: &AtomicUsize) -> usize {
+        hazard::Guard::protect(self, slot, link)
+    }
+
+    fn protect_ptr<T>(&self, slot: usize, link: &AtomicPtr<T>) -> *mut T {
+        hazard::Guard::protect_ptr(self, slot, link)
+    }
+
+    fn copy(&self, slot: usize, ptr: usize) {
+        self.set(slot, ptr);
+    }
+
+    unsafe fn retire<T: Send + 'static>(&self, ptr: *mut T) {
+        hazard::Guard::retire(self, ptr)
+    }
+}
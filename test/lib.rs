@@ -15,3 +15,16 @@ mod test_primitives;
 mod test_array_queue;
 mod test_linked_queue;
 mod test_maps;
+mod test_epoch;
+mod test_hazard;
+mod test_channel;
+mod test_select;
+mod test_lock_free_array_queue;
+mod test_lock_free_linked_queue;
+mod test_spsc_queue;
+mod test_mpsc_queue;
+mod test_priority_queue;
+mod test_delay_queue;
+mod test_synchronous_queue;
+mod test_linked_deque;
+mod test_deque;
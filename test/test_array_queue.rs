@@ -1,5 +1,5 @@
 pub use concrust::queue::ArrayBlockingQueue;
-pub use concrust::queue::BlockingQueue;
+pub use concrust::queue::{BlockingQueue, Closed};
 
 pub use std::sync::Arc;
 pub use std::sync::atomic::{AtomicBool, Ordering};
@@ -8,7 +8,7 @@ pub use std::time::Duration;
 pub use std::thread;
 pub use std::sync::mpsc;
 
-pub use expectest::prelude::{be_equal_to, be_true, be_false, be_some, be_ok};
+pub use expectest::prelude::{be_equal_to, be_true, be_false, be_some, be_none, be_ok, be_err};
 
 describe! bounded_blocking_queue_test {
 
@@ -44,7 +44,7 @@ describe! bounded_blocking_queue_test {
     }
 
     it "should increase size when insert into queue" {
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
 
         expect!(queue.is_empty()).not_to(be_true());
     }
@@ -55,7 +55,7 @@ describe! bounded_blocking_queue_test {
     }
 
     it "should contain value that was equeued" {
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
 
         expect!(queue.contains(1)).to(be_true());
     }
@@ -65,10 +65,10 @@ describe! bounded_blocking_queue_test {
     }
 
     it "should contain values that were enqueued" {
-        queue.enqueue(10);
-        queue.enqueue(20);
-        queue.enqueue(30);
-        queue.enqueue(40);
+        queue.enqueue(10).unwrap();
+        queue.enqueue(20).unwrap();
+        queue.enqueue(30).unwrap();
+        queue.enqueue(40).unwrap();
 
         expect!(queue.contains(10)).to(be_true());
         expect!(queue.contains(20)).to(be_true());
@@ -77,35 +77,35 @@ describe! bounded_blocking_queue_test {
     }
 
     it "should decrise size when remove from queue" {
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
 
-        queue.dequeue();
+        queue.dequeue().unwrap();
 
         expect!(queue.is_empty()).to(be_true());
     }
 
     it "should dequeue enqueued value" {
-        queue.enqueue(10);
+        queue.enqueue(10).unwrap();
 
-        expect!(queue.dequeue()).to(be_equal_to(10));
+        expect!(queue.dequeue()).to(be_ok().value(10));
 
-        queue.enqueue(20);
+        queue.enqueue(20).unwrap();
 
-        expect!(queue.dequeue()).to(be_equal_to(20));
+        expect!(queue.dequeue()).to(be_ok().value(20));
 
-        queue.enqueue(30);
+        queue.enqueue(30).unwrap();
 
-        expect!(queue.dequeue()).to(be_equal_to(30));
+        expect!(queue.dequeue()).to(be_ok().value(30));
     }
 
     it "should dequeue first enqueued value" {
-        queue.enqueue(10);
-        queue.enqueue(20);
-        queue.enqueue(30);
+        queue.enqueue(10).unwrap();
+        queue.enqueue(20).unwrap();
+        queue.enqueue(30).unwrap();
 
-        expect!(queue.dequeue()).to(be_equal_to(10));
-        expect!(queue.dequeue()).to(be_equal_to(20));
-        expect!(queue.dequeue()).to(be_equal_to(30));
+        expect!(queue.dequeue()).to(be_ok().value(10));
+        expect!(queue.dequeue()).to(be_ok().value(20));
+        expect!(queue.dequeue()).to(be_ok().value(30));
     }
 
     it "should insert offered value if queue not full" {
@@ -114,14 +114,22 @@ describe! bounded_blocking_queue_test {
     }
 
     it "should peek first element but not delete" {
-        queue.enqueue(1);
-        queue.enqueue(2);
-        queue.enqueue(3);
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.enqueue(3).unwrap();
 
         expect!(queue.peek()).to(be_some().value(1));
         expect!(queue.peek()).to(be_some().value(1));
     }
 
+    it "should peek clone of value which stays in queue" {
+        let queue = ArrayBlockingQueue::with_capacity(CAPACITY);
+        queue.enqueue(String::from("first")).unwrap();
+
+        expect!(queue.peek()).to(be_some().value(String::from("first")));
+        expect!(queue.dequeue()).to(be_ok().value(String::from("first")));
+    }
+
     it "should calculate correct size when alot insertions and deletions" {
         for iter in 0..5 {
             let size = queue.len();
@@ -137,8 +145,8 @@ describe! bounded_blocking_queue_test {
     it "should enqueue dequeue more than capacity times" {
         for i in 0..2*CAPACITY {
             let elem = i as i32;
-            queue.enqueue(elem);
-            expect!(queue.dequeue()).to(be_equal_to(elem));
+            queue.enqueue(elem).unwrap();
+            expect!(queue.dequeue()).to(be_ok().value(elem));
         }
     }
 
@@ -152,12 +160,213 @@ describe! bounded_blocking_queue_test {
     it "should reject offered value when queue is full" {
         for i in 0..CAPACITY {
             let elem = i as i32;
-            (queue.enqueue(elem));
+            queue.enqueue(elem).unwrap();
         }
 
         expect!(queue.offer(1)).to(be_false());
     }
 
+    it "should return value back when try to enqueue into full queue" {
+        enqeue_times(CAPACITY as i32, &queue);
+
+        expect!(queue.try_enqueue(1)).to(be_err().value(1));
+        expect!(queue.len()).to(be_equal_to(CAPACITY));
+    }
+
+    it "should enqueue value when try to enqueue into not full queue" {
+        expect!(queue.try_enqueue(1)).to(be_ok());
+        expect!(queue.contains(1)).to(be_true());
+    }
+
+    it "should poll first enqueued value" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        expect!(queue.poll()).to(be_some().value(1));
+        expect!(queue.len()).to(be_equal_to(1));
+    }
+
+    it "should return none when poll empty queue" {
+        expect!(queue.poll()).to(be_none());
+    }
+
+    it "should never block when many threads offer into almost full queue" {
+        const NUMBER_OF_THREADS: usize = 8;
+        enqeue_times(CAPACITY as i32 - 1, &queue);
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = queue.clone();
+            results.push(thread::spawn(move || data.offer(1)));
+        }
+
+        let accepted = results.into_iter().map(|jh| jh.join().unwrap()).filter(|&accepted| accepted).count();
+        expect!(accepted).to(be_equal_to(1));
+        expect!(queue.len()).to(be_equal_to(CAPACITY));
+    }
+
+    it "should return offered value back when queue is full until timeout" {
+        enqeue_times(CAPACITY as i32, &queue);
+
+        expect!(queue.offer_timeout(1, Duration::from_millis(10))).to(be_err().value(1));
+        expect!(queue.len()).to(be_equal_to(CAPACITY));
+    }
+
+    it "should accept value offered with timeout when queue is not full" {
+        expect!(queue.offer_timeout(1, Duration::from_millis(10))).to(be_ok());
+        expect!(queue.dequeue()).to(be_ok().value(1));
+    }
+
+    it "should accept value offered with timeout when other thread dequeues" {
+        enqeue_times(CAPACITY as i32, &queue);
+        let data = queue.clone();
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                data.dequeue().unwrap();
+            }
+        );
+
+        expect!(queue.offer_timeout(1, Duration::from_secs(10))).to(be_ok());
+        expect!(jh.join()).to(be_ok());
+    }
+
+    it "should return none when poll times out on empty queue" {
+        expect!(queue.poll_timeout(Duration::from_millis(10))).to(be_none());
+    }
+
+    it "should poll value enqueued by other thread before timeout" {
+        let data = queue.clone();
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                data.enqueue(1).unwrap();
+            }
+        );
+
+        expect!(queue.poll_timeout(Duration::from_secs(10))).to(be_some().value(1));
+        expect!(jh.join()).to(be_ok());
+    }
+
+    it "should reject values enqueued into closed queue" {
+        queue.close();
+
+        expect!(queue.is_closed()).to(be_true());
+        expect!(queue.enqueue(1)).to(be_err().value(1));
+        expect!(queue.try_enqueue(2)).to(be_err().value(2));
+        expect!(queue.offer_timeout(3, Duration::from_millis(10))).to(be_err().value(3));
+        expect!(queue.offer(4)).to(be_false());
+        expect!(queue.is_empty()).to(be_true());
+    }
+
+    it "should dequeue values left in closed queue" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.close();
+
+        expect!(queue.dequeue()).to(be_ok().value(1));
+        expect!(queue.poll()).to(be_some().value(2));
+        expect!(queue.dequeue()).to(be_err().value(Closed));
+        expect!(queue.poll_timeout(Duration::from_secs(10))).to(be_none());
+    }
+
+    it "should wake up consumers waiting on empty queue when it is closed" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = queue.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        queue.close();
+
+        for jh in results {
+            expect!(jh.join().unwrap()).to(be_err().value(Closed));
+        }
+    }
+
+    it "should wake up producers waiting on full queue when it is closed" {
+        enqeue_times(CAPACITY as i32, &queue);
+        let data = queue.clone();
+        let jh = thread::spawn(move || data.enqueue(10));
+
+        thread::sleep(Duration::from_millis(100));
+        queue.close();
+
+        expect!(jh.join().unwrap()).to(be_err().value(10));
+        expect!(queue.len()).to(be_equal_to(CAPACITY));
+    }
+
+    it "should drain up to max values in order" {
+        enqeue_times(5, &queue);
+        let mut target = Vec::new();
+
+        expect!(queue.drain_to(&mut target, 3)).to(be_equal_to(3));
+        expect!(target.clone()).to(be_equal_to(vec![0, 1, 2]));
+        expect!(queue.len()).to(be_equal_to(2));
+        expect!(queue.drain_to(&mut target, 10)).to(be_equal_to(2));
+        expect!(target).to(be_equal_to(vec![0, 1, 2, 3, 4]));
+        expect!(queue.is_empty()).to(be_true());
+    }
+
+    it "should not block when drain empty queue" {
+        let mut target = Vec::new();
+
+        expect!(queue.drain_to(&mut target, 10)).to(be_equal_to(0));
+        expect!(target.is_empty()).to(be_true());
+    }
+
+    it "should enqueue all values in order" {
+        expect!(queue.enqueue_all(vec![1, 2, 3])).to(be_ok());
+
+        expect!(queue.len()).to(be_equal_to(3));
+        expect!(queue.dequeue()).to(be_ok().value(1));
+        expect!(queue.dequeue()).to(be_ok().value(2));
+        expect!(queue.dequeue()).to(be_ok().value(3));
+    }
+
+    it "should return values which were not enqueued into closed queue" {
+        queue.enqueue(1).unwrap();
+        queue.close();
+
+        expect!(queue.enqueue_all(vec![2, 3])).to(be_err().value(vec![2, 3]));
+        expect!(queue.len()).to(be_equal_to(1));
+    }
+
+    it "should wait for free space when enqueue more values than capacity" {
+        let data = queue.clone();
+        let jh = thread::spawn(move || data.enqueue_all(0..(CAPACITY as i32 + 4)));
+
+        let mut target = Vec::new();
+        while target.len() < CAPACITY + 4 {
+            if queue.drain_to(&mut target, 4) == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        expect!(jh.join().unwrap()).to(be_ok());
+        expect!(target).to(be_equal_to((0..(CAPACITY as i32 + 4)).collect::<Vec<i32>>()));
+    }
+
+    it "should wake up all consumers once when enqueue all values" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = queue.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        expect!(queue.enqueue_all(vec![1; NUMBER_OF_THREADS])).to(be_ok());
+
+        for jh in results {
+            expect!(jh.join().unwrap()).to(be_ok().value(1));
+        }
+    }
+
     it "should wait when queue is empty" {
         const NUMBER_OF_THREADS: usize = 10;
         let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
@@ -166,20 +375,20 @@ describe! bounded_blocking_queue_test {
             let data = queue.clone();
             let jh = thread::spawn(
                 move || {
-                    expect!(data.dequeue()).to(be_equal_to(1));
-                    data.enqueue(1);
+                    expect!(data.dequeue()).to(be_ok().value(1));
+                    data.enqueue(1).unwrap();
                 }
             );
             results.push(jh);
         }
 
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
 
         for jh in results {
             expect!(jh.join()).to(be_ok());
         }
 
-        expect!(queue.dequeue()).to(be_equal_to(1));
+        expect!(queue.dequeue()).to(be_ok().value(1));
     }
 
     it "should notify threads when offer value to queue" {
@@ -190,7 +399,7 @@ describe! bounded_blocking_queue_test {
             let data = queue.clone();
             let jh = thread::spawn(
                 move || {
-                    expect!(data.dequeue()).to(be_equal_to(1));
+                    expect!(data.dequeue()).to(be_ok().value(1));
                     expect!(data.offer(1)).to(be_true());
                 }
             );
@@ -204,13 +413,13 @@ describe! bounded_blocking_queue_test {
             expect!(jh.join()).to(be_ok());
         }
 
-        expect!(queue.dequeue()).to(be_equal_to(1));
+        expect!(queue.dequeue()).to(be_ok().value(1));
     }
 
     it "should wait when queue is full" {
         const NUMBER_OF_THREADS: usize = CAPACITY-1;
         for _ in 0..CAPACITY {
-            queue.enqueue(1);
+            queue.enqueue(1).unwrap();
         }
         let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
 
@@ -218,15 +427,15 @@ describe! bounded_blocking_queue_test {
             let data = queue.clone();
             let jh = thread::spawn(
                 move || {
-                    data.enqueue(10);
-                    expect!(data.dequeue()).to(be_equal_to(1));
+                    data.enqueue(10).unwrap();
+                    expect!(data.dequeue()).to(be_ok().value(1));
                 }
             );
             results.push(jh);
         }
 
         thread::sleep(Duration::from_millis(100));
-        expect!(queue.dequeue()).to(be_equal_to(1));
+        expect!(queue.dequeue()).to(be_ok().value(1));
 
         for jh in results {
             expect!(jh.join()).to(be_ok());
@@ -238,12 +447,12 @@ describe! bounded_blocking_queue_test {
 
 pub fn enqeue_times(times: i32, queue: &BlockingQueue<i32>) {
     for i in 0..times {
-        queue.enqueue(i);
+        queue.enqueue(i).unwrap();
     }
 }
 
 pub fn dequeue_times(times: i32, queue: &BlockingQueue<i32>) {
     for i in 0..times {
-        queue.dequeue();
+        queue.dequeue().unwrap();
     }
 }
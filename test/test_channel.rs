@@ -0,0 +1,129 @@
+pub use concrust::queue::{channel, ArrayBlockingQueue, Closed};
+
+pub use std::time::Duration;
+
+pub use std::thread;
+
+describe! channel_test {
+
+    before_each {
+        const CAPACITY: usize = 16;
+        let (sender, receiver) = channel::<i32>(CAPACITY);
+    }
+
+    it "should receive values in order they were sent" {
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+
+        assert_eq!(receiver.len(), 2);
+        assert_eq!(receiver.recv(), Ok(1));
+        assert_eq!(receiver.recv(), Ok(2));
+        assert!(receiver.is_empty());
+    }
+
+    it "should not send value into full channel" {
+        for i in 0..CAPACITY as i32 {
+            sender.send(i).unwrap();
+        }
+
+        assert_eq!(sender.try_send(100), Err(100));
+        assert_eq!(sender.send_timeout(200, Duration::from_millis(10)), Err(200));
+    }
+
+    it "should return none when try to receive from empty channel" {
+        assert_eq!(receiver.try_recv(), None);
+        assert_eq!(receiver.recv_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should receive values left in channel when all senders are dropped" {
+        let other = sender.clone();
+        sender.send(1).unwrap();
+        other.send(2).unwrap();
+        drop(sender);
+
+        assert!(!receiver.is_disconnected());
+        drop(other);
+
+        assert!(receiver.is_disconnected());
+        assert_eq!(receiver.recv(), Ok(1));
+        assert_eq!(receiver.recv(), Ok(2));
+        assert_eq!(receiver.recv(), Err(Closed));
+    }
+
+    it "should return value back when all receivers are dropped" {
+        let other = receiver.clone();
+        drop(receiver);
+
+        assert_eq!(sender.send(1), Ok(()));
+        drop(other);
+
+        assert!(sender.is_disconnected());
+        assert_eq!(sender.send(2), Err(2));
+        assert_eq!(sender.try_send(3), Err(3));
+    }
+
+    it "should wake up receivers when all senders are dropped" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let receiver = receiver.clone();
+            results.push(thread::spawn(move || receiver.recv()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        drop(sender);
+
+        for jh in results {
+            assert_eq!(jh.join().unwrap(), Err(Closed));
+        }
+    }
+
+    it "should wake up sender waiting on full channel when all receivers are dropped" {
+        for i in 0..CAPACITY as i32 {
+            sender.send(i).unwrap();
+        }
+        let jh = thread::spawn(move || sender.send(100));
+
+        thread::sleep(Duration::from_millis(100));
+        drop(receiver);
+
+        assert_eq!(jh.join().unwrap(), Err(100));
+    }
+
+    it "should iterate over values until all senders are dropped" {
+        const NUMBER_OF_THREADS: i32 = 4;
+        const VALUES_PER_THREAD: i32 = 100;
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let sender = sender.clone();
+            results.push(thread::spawn(
+                move || {
+                    for i in 0..VALUES_PER_THREAD {
+                        sender.send(t * VALUES_PER_THREAD + i).unwrap();
+                    }
+                }
+            ));
+        }
+        drop(sender);
+
+        let mut received = receiver.iter().collect::<Vec<i32>>();
+        received.sort();
+
+        assert_eq!(received, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).collect::<Vec<i32>>());
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+    }
+
+    it "should split queue into sender and receiver" {
+        let queue: ArrayBlockingQueue<i32> = ArrayBlockingQueue::new();
+        let (sender, receiver) = queue.split();
+        sender.send(1).unwrap();
+        drop(sender);
+
+        assert_eq!(receiver.recv(), Ok(1));
+        assert_eq!(receiver.recv(), Err(Closed));
+    }
+}
@@ -0,0 +1,135 @@
+pub use concrust::queue::{DelayQueue, Delay, BlockingQueue, Closed};
+
+pub use std::sync::Arc;
+pub use std::time::{Duration, Instant};
+
+pub use std::thread;
+
+describe! delay_queue_test {
+
+    before_each {
+        let queue: DelayQueue<Delay<i32>> = DelayQueue::new();
+    }
+
+    it "should create empty queue" {
+        assert!(queue.is_empty());
+        assert_eq!(queue.poll(), None);
+    }
+
+    it "should not release value before its deadline" {
+        queue.enqueue(Delay::after(1, Duration::from_secs(60))).unwrap();
+
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.expired(), 0);
+        assert_eq!(queue.poll(), None);
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+        assert_eq!(queue.peek().map(|delay| delay.value), Some(1));
+    }
+
+    it "should release values in order of their deadlines" {
+        let now = Instant::now();
+        queue.enqueue(Delay::at(3, now - Duration::from_millis(10))).unwrap();
+        queue.enqueue(Delay::at(1, now - Duration::from_millis(30))).unwrap();
+        queue.enqueue(Delay::at(2, now - Duration::from_millis(20))).unwrap();
+
+        assert_eq!(queue.expired(), 3);
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Ok(1));
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Ok(2));
+        assert_eq!(queue.poll().map(|delay| delay.value), Some(3));
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue = DelayQueue::new();
+        queue.enqueue(Delay::after(String::from("first"), Duration::from_millis(0))).unwrap();
+
+        assert_eq!(queue.peek().map(|delay| delay.value), Some(String::from("first")));
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Ok(String::from("first")));
+        assert!(queue.peek().is_none());
+    }
+
+    it "should block until deadline passes" {
+        let start = Instant::now();
+        queue.enqueue(Delay::after(1, Duration::from_millis(100))).unwrap();
+
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Ok(1));
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    it "should drain only expired values" {
+        queue.enqueue_all(vec![
+            Delay::after(1, Duration::from_millis(0)),
+            Delay::after(2, Duration::from_secs(60)),
+            Delay::after(3, Duration::from_millis(0))
+        ]).unwrap();
+        let mut target = Vec::new();
+
+        assert_eq!(queue.drain_to(&mut target, 10), 2);
+        assert_eq!(target.into_iter().map(|delay| delay.value).collect::<Vec<i32>>(), vec![1, 3]);
+        assert_eq!(queue.len(), 1);
+    }
+
+    it "should wake up consumer when earlier value is enqueued" {
+        queue.enqueue(Delay::after(1, Duration::from_secs(60))).unwrap();
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue().map(|delay| delay.value));
+
+        thread::sleep(Duration::from_millis(100));
+        arc.enqueue(Delay::after(2, Duration::from_millis(50))).unwrap();
+
+        assert_eq!(jh.join().unwrap(), Ok(2));
+        assert_eq!(arc.len(), 1);
+    }
+
+    it "should wake up consumer waiting on empty queue when it is closed" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue().map(|delay| delay.value));
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(Closed));
+        assert!(arc.enqueue(Delay::after(1, Duration::from_millis(0))).is_err());
+    }
+
+    it "should release values left in closed queue when their deadlines pass" {
+        queue.enqueue(Delay::after(1, Duration::from_millis(50))).unwrap();
+        queue.close();
+
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Ok(1));
+        assert_eq!(queue.dequeue().map(|delay| delay.value), Err(Closed));
+    }
+
+    it "should release every value once to several consumers" {
+        const NUMBER_OF_THREADS: usize = 4;
+        const VALUES: i32 = 100;
+        let arc = Arc::new(queue);
+        for i in 0..VALUES {
+            arc.enqueue(Delay::after(i, Duration::from_millis((i % 10) as u64 * 5))).unwrap();
+        }
+        arc.close();
+        let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            consumers.push(thread::spawn(
+                move || {
+                    let mut values = Vec::new();
+                    while let Ok(delay) = data.dequeue() {
+                        assert!(delay.deadline <= Instant::now());
+                        values.push(delay.value);
+                    }
+                    values
+                }
+            ));
+        }
+        let mut values = Vec::new();
+        for jh in consumers {
+            values.extend(jh.join().unwrap());
+        }
+        values.sort();
+
+        assert_eq!(values, (0..VALUES).collect::<Vec<i32>>());
+    }
+}
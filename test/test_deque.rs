@@ -0,0 +1,160 @@
+pub use concrust::deque::{Worker, Stealer, Steal};
+pub use concrust::reclaim::Hazards;
+
+pub use std::sync::Arc;
+pub use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use std::thread;
+
+describe! work_stealing_deque_test {
+
+    before_each {
+        let worker: Worker<i32> = Worker::new();
+        let stealer = worker.stealer();
+    }
+
+    it "should create empty deque" {
+        assert!(worker.is_empty());
+        assert!(stealer.is_empty());
+        assert_eq!(worker.pop(), None);
+        assert_eq!(stealer.steal(), Steal::Empty);
+    }
+
+    it "should pop values in LIFO order" {
+        worker.push(1);
+        worker.push(2);
+        worker.push(3);
+
+        assert_eq!(worker.len(), 3);
+        assert_eq!(worker.pop(), Some(3));
+        assert_eq!(worker.pop(), Some(2));
+        assert_eq!(worker.pop(), Some(1));
+        assert_eq!(worker.pop(), None);
+    }
+
+    it "should steal values in FIFO order" {
+        worker.push(1);
+        worker.push(2);
+        worker.push(3);
+
+        assert_eq!(stealer.len(), 3);
+        assert_eq!(stealer.steal(), Steal::Success(1));
+        assert_eq!(stealer.clone().steal(), Steal::Success(2));
+        assert_eq!(worker.pop(), Some(3));
+        assert_eq!(stealer.steal(), Steal::Empty);
+    }
+
+    it "should grow when buffer is full" {
+        for val in 0..1000 {
+            worker.push(val);
+        }
+
+        assert_eq!(worker.len(), 1000);
+        assert_eq!(stealer.steal(), Steal::Success(0));
+        assert_eq!(worker.pop(), Some(999));
+        assert_eq!(worker.len(), 998);
+    }
+
+    it "should drop values left in deque when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let worker: Worker<Arc<i32>> = Worker::new();
+            let stealer = worker.stealer();
+            for _ in 0..20 {
+                worker.push(value.clone());
+            }
+            worker.pop().unwrap();
+            match stealer.steal() {
+                Steal::Success(_) => {},
+                _ => panic!("value should be stolen"),
+            }
+            assert_eq!(Arc::strong_count(&value), 19);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should take every value exactly once by owner and stealers" {
+        let done = Arc::new(AtomicBool::new(false));
+        let mut thieves = Vec::new();
+        for _ in 0..3 {
+            let stealer = stealer.clone();
+            let done = done.clone();
+            thieves.push(thread::spawn(move || {
+                let mut stolen = Vec::new();
+                loop {
+                    match stealer.steal() {
+                        Steal::Success(val) => stolen.push(val),
+                        Steal::Retry => {},
+                        Steal::Empty => if done.load(Ordering::SeqCst) { break },
+                    }
+                }
+                stolen
+            }));
+        }
+        let mut all = Vec::new();
+        for val in 0..10000 {
+            worker.push(val);
+            if val % 3 == 0 {
+                all.extend(worker.pop());
+            }
+        }
+        while let Some(val) = worker.pop() {
+            all.push(val);
+        }
+        done.store(true, Ordering::SeqCst);
+        for jh in thieves {
+            all.extend(jh.join().unwrap());
+        }
+        all.sort();
+
+        assert_eq!(all, (0..10000).collect::<Vec<i32>>());
+    }
+
+    it "should take every value exactly once with hazard pointers" {
+        let worker: Worker<i32, Hazards> = Worker::new();
+        let stealer = worker.stealer();
+        let done = Arc::new(AtomicBool::new(false));
+        let mut thieves = Vec::new();
+        for _ in 0..3 {
+            let stealer = stealer.clone();
+            let done = done.clone();
+            thieves.push(thread::spawn(move || {
+                let mut stolen = Vec::new();
+                loop {
+                    match stealer.steal() {
+                        Steal::Success(val) => stolen.push(val),
+                        Steal::Retry => {},
+                        Steal::Empty => if done.load(Ordering::SeqCst) { break },
+                    }
+                }
+                stolen
+            }));
+        }
+        let mut all = Vec::new();
+        for val in 0..10000 {
+            worker.push(val);
+        }
+        while let Some(val) = worker.pop() {
+            all.push(val);
+        }
+        done.store(true, Ordering::SeqCst);
+        for jh in thieves {
+            all.extend(jh.join().unwrap());
+        }
+        all.sort();
+
+        assert_eq!(all, (0..10000).collect::<Vec<i32>>());
+    }
+
+    it "should move worker to another thread" {
+        worker.push(1);
+        let jh = thread::spawn(move || {
+            worker.push(2);
+            worker.pop()
+        });
+
+        assert_eq!(jh.join().unwrap(), Some(2));
+        assert_eq!(stealer.steal(), Steal::Success(1));
+    }
+}
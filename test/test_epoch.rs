@@ -0,0 +1,97 @@
+pub use concrust::epoch;
+
+pub use std::thread;
+pub use std::time::Duration;
+pub use std::sync::{Arc, Barrier};
+pub use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Counted {
+    counter: Arc<AtomicUsize>
+}
+
+impl Drop for Counted {
+
+    fn drop(&mut self) {
+        self.counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn retire(counter: &Arc<AtomicUsize>) {
+    let guard = epoch::pin();
+    let ptr = Box::into_raw(Box::new(Counted { counter: counter.clone() }));
+    unsafe { guard.defer_destroy(ptr); }
+}
+
+/// Flush until garbage is destroyed, threads of other tests could be pinned meanwhile
+pub fn flush_until(counter: &Arc<AtomicUsize>, expected: usize) -> bool {
+    for _ in 0..1000 {
+        epoch::pin().flush();
+        if counter.load(Ordering::Relaxed) == expected {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    false
+}
+
+describe! epoch_tests {
+
+    before_each {
+        let counter = Arc::new(AtomicUsize::new(0));
+    }
+
+    it "should destroy retired object when no thread is pinned" {
+        retire(&counter);
+
+        assert!(flush_until(&counter, 1));
+    }
+
+    it "should not destroy retired object while another thread is pinned" {
+        let pinned = Arc::new(Barrier::new(2));
+        let release = Arc::new(Barrier::new(2));
+        let jh = {
+            let pinned = pinned.clone();
+            let release = release.clone();
+            thread::spawn(
+                move || {
+                    let _guard = epoch::pin();
+                    pinned.wait();
+                    release.wait();
+                }
+            )
+        };
+        pinned.wait();
+        retire(&counter);
+        for _ in 0..10 {
+            epoch::pin().flush();
+        }
+
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        release.wait();
+        assert!(jh.join().is_ok());
+        assert!(flush_until(&counter, 1));
+    }
+
+    it "should destroy garbage left by exited thread" {
+        let jh = {
+            let counter = counter.clone();
+            thread::spawn(move || retire(&counter))
+        };
+        assert!(jh.join().is_ok());
+
+        assert!(flush_until(&counter, 1));
+    }
+
+    it "should allow nested guards" {
+        let outer = epoch::pin();
+        {
+            let _inner = epoch::pin();
+            retire(&counter);
+        }
+        outer.flush();
+
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        drop(outer);
+        assert!(flush_until(&counter, 1));
+    }
+}
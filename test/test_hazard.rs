@@ -0,0 +1,139 @@
+pub use concrust::hazard;
+
+pub use std::thread;
+pub use std::time::Duration;
+pub use std::sync::{Arc, Barrier};
+pub use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Counted {
+    counter: Arc<AtomicUsize>
+}
+
+impl Drop for Counted {
+
+    fn drop(&mut self) {
+        self.counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn counted(counter: &Arc<AtomicUsize>) -> *mut Counted {
+    Box::into_raw(Box::new(Counted { counter: counter.clone() }))
+}
+
+/// Scan until garbage is destroyed, objects of other threads could be retired meanwhile
+pub fn flush_until(counter: &Arc<AtomicUsize>, expected: usize) -> bool {
+    for _ in 0..1000 {
+        hazard::guard().flush();
+        if counter.load(Ordering::Relaxed) == expected {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    false
+}
+
+describe! hazard_tests {
+
+    before_each {
+        let counter = Arc::new(AtomicUsize::new(0));
+    }
+
+    it "should protect loaded pointer" {
+        let link = AtomicUsize::new(counted(&counter) as usize);
+        let guard = hazard::guard();
+
+        assert_eq!(guard.protect(0, &link), link.load(Ordering::Relaxed));
+        unsafe { guard.retire(link.swap(0, Ordering::Relaxed) as *mut Counted); }
+        guard.flush();
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+        guard.clear(0);
+        assert!(flush_until(&counter, 1));
+    }
+
+    it "should destroy retired objects which are not protected" {
+        let guard = hazard::guard();
+        for _ in 0..100 {
+            unsafe { guard.retire(counted(&counter)); }
+        }
+
+        assert!(flush_until(&counter, 100));
+    }
+
+    it "should destroy garbage while another thread holding hazard is parked" {
+        let protected = Arc::new(AtomicUsize::new(0));
+        let link = Arc::new(AtomicUsize::new(counted(&protected) as usize));
+        let parked = Arc::new(Barrier::new(2));
+        let release = Arc::new(Barrier::new(2));
+        let jh = {
+            let link = link.clone();
+            let parked = parked.clone();
+            let release = release.clone();
+            thread::spawn(
+                move || {
+                    let guard = hazard::guard();
+                    guard.protect(0, &link);
+                    parked.wait();
+                    release.wait();
+                }
+            )
+        };
+        parked.wait();
+        {
+            let guard = hazard::guard();
+            unsafe { guard.retire(link.swap(0, Ordering::Relaxed) as *mut Counted); }
+            for _ in 0..1000 {
+                unsafe { guard.retire(counted(&counter)); }
+            }
+        }
+
+        assert!(flush_until(&counter, 1000));
+        assert_eq!(protected.load(Ordering::Relaxed), 0);
+        release.wait();
+        assert!(jh.join().is_ok());
+        assert!(flush_until(&protected, 1));
+    }
+
+    it "should destroy garbage left by exited thread" {
+        let jh = {
+            let counter = counter.clone();
+            thread::spawn(
+                move || {
+                    let guard = hazard::guard();
+                    let link = AtomicUsize::new(counted(&counter) as usize);
+                    guard.protect(0, &link);
+                    unsafe { guard.retire(link.load(Ordering::Relaxed) as *mut Counted); }
+                    guard.flush();
+                }
+            )
+        };
+        assert!(jh.join().is_ok());
+
+        assert!(flush_until(&counter, 1));
+    }
+
+    it "should keep garbage left by exited thread while it is protected" {
+        let link = Arc::new(AtomicUsize::new(counted(&counter) as usize));
+        let guard = hazard::guard();
+        guard.protect(0, &link);
+        let jh = {
+            let link = link.clone();
+            thread::spawn(
+                move || {
+                    let guard = hazard::guard();
+                    unsafe { guard.retire(link.swap(0, Ordering::Relaxed) as *mut Counted); }
+                }
+            )
+        };
+        assert!(jh.join().is_ok());
+        guard.flush();
+
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+        guard.clear(0);
+        assert!(flush_until(&counter, 1));
+    }
+
+    failing "should not give more than four guards to a thread" {
+        let _guards = (0..5).map(|_| hazard::guard()).collect::<Vec<_>>();
+    }
+}
@@ -0,0 +1,179 @@
+pub use concrust::queue::{LinkedBlockingDeque, BlockingQueue, Closed};
+
+pub use std::sync::Arc;
+pub use std::time::{Duration, Instant};
+
+pub use std::thread;
+
+describe! linked_blocking_deque_test {
+
+    before_each {
+        let deque: LinkedBlockingDeque<i32> = LinkedBlockingDeque::new();
+    }
+
+    it "should create empty unbounded deque" {
+        assert!(deque.is_empty());
+        assert_eq!(deque.capacity(), None);
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.pop_back(), None);
+    }
+
+    it "should push and pop at both ends" {
+        deque.push_back(2).unwrap();
+        deque.push_front(1).unwrap();
+        deque.push_back(3).unwrap();
+
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.peek_front(), Some(1));
+        assert_eq!(deque.peek_back(), Some(3));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert!(deque.is_empty());
+    }
+
+    it "should peek clones of values which stay in deque" {
+        let value = Arc::new(1);
+        {
+            let deque = LinkedBlockingDeque::new();
+            deque.push_back(value.clone()).unwrap();
+            deque.push_back(value.clone()).unwrap();
+
+            assert_eq!(deque.peek_front().map(|val| *val), Some(1));
+            assert_eq!(deque.peek_back().map(|val| *val), Some(1));
+            assert_eq!(deque.peek().map(|val| *val), Some(1));
+            assert_eq!(Arc::strong_count(&value), 3);
+
+            deque.pop_front().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should put urgent value before queued values" {
+        deque.enqueue_all(vec![1, 2, 3]).unwrap();
+        deque.put_first(0).unwrap();
+
+        assert_eq!(deque.dequeue(), Ok(0));
+        assert_eq!(deque.dequeue(), Ok(1));
+    }
+
+    it "should reject push into full bounded deque" {
+        let deque = LinkedBlockingDeque::with_capacity(2);
+        deque.push_back(1).unwrap();
+        deque.push_front(2).unwrap();
+
+        assert_eq!(deque.capacity(), Some(2));
+        assert_eq!(deque.push_back(3), Err(3));
+        assert_eq!(deque.push_front(4), Err(4));
+        assert!(!deque.offer(5));
+    }
+
+    it "should block put into full bounded deque until value is taken" {
+        let deque = LinkedBlockingDeque::with_capacity(1);
+        deque.put_last(1).unwrap();
+        let arc = Arc::new(deque);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.put_first(2));
+
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(arc.take_last(), Ok(1));
+        assert_eq!(jh.join().unwrap(), Ok(()));
+        assert_eq!(arc.take_first(), Ok(2));
+    }
+
+    it "should time out offer into full bounded deque" {
+        let deque = LinkedBlockingDeque::with_capacity(1);
+        deque.enqueue(1).unwrap();
+        let start = Instant::now();
+
+        assert_eq!(deque.offer_timeout(2, Duration::from_millis(50)), Err(2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    it "should block take until value is put" {
+        let arc = Arc::new(deque);
+        let first = arc.clone();
+        let last = arc.clone();
+        let jh_first = thread::spawn(move || first.take_first());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.put_last(1).unwrap();
+
+        assert_eq!(jh_first.join().unwrap(), Ok(1));
+
+        let jh_last = thread::spawn(move || last.take_last());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.put_first(2).unwrap();
+
+        assert_eq!(jh_last.join().unwrap(), Ok(2));
+    }
+
+    it "should time out poll on empty deque" {
+        let start = Instant::now();
+
+        assert_eq!(deque.poll_timeout(Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    it "should drain values from the front" {
+        deque.enqueue_all(vec![1, 2, 3]).unwrap();
+        let mut target = Vec::new();
+
+        assert_eq!(deque.drain_to(&mut target, 2), 2);
+        assert_eq!(target, vec![1, 2]);
+        assert_eq!(deque.len(), 1);
+    }
+
+    it "should wake up waiting threads when closed" {
+        let arc = Arc::new(deque);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.take_last());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(Closed));
+        assert_eq!(arc.put_first(1), Err(1));
+        assert_eq!(arc.push_back(2), Err(2));
+    }
+
+    it "should take values left in closed deque" {
+        deque.enqueue_all(vec![1, 2]).unwrap();
+        deque.close();
+
+        assert_eq!(deque.take_last(), Ok(2));
+        assert_eq!(deque.take_first(), Ok(1));
+        assert_eq!(deque.take_first(), Err(Closed));
+    }
+
+    it "should let owner work from the back while others steal from the front" {
+        deque.enqueue_all(0..1000).unwrap();
+        let arc = Arc::new(deque);
+        let mut thieves = Vec::new();
+        for _ in 0..3 {
+            let data = arc.clone();
+            thieves.push(thread::spawn(move || {
+                let mut stolen = Vec::new();
+                while let Some(val) = data.pop_front() {
+                    stolen.push(val);
+                }
+                stolen
+            }));
+        }
+        let mut owned = Vec::new();
+        while let Some(val) = arc.pop_back() {
+            owned.push(val);
+        }
+        let mut all = owned;
+        for jh in thieves {
+            all.extend(jh.join().unwrap());
+        }
+        all.sort();
+
+        assert_eq!(all, (0..1000).collect::<Vec<i32>>());
+    }
+}
@@ -1,4 +1,5 @@
 pub use concrust::queue::UnboundedBlockingQueue;
+pub use concrust::queue::{BlockingQueue, Closed};
 
 pub use std::sync::Arc;
 pub use std::sync::atomic::{AtomicBool, Ordering};
@@ -20,29 +21,29 @@ describe! unbounded_blocking_queue_test {
 
     it "should increase queue size when enqueue value" {
         let old_size = queue.size();
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
 
         assert_eq!(queue.size(), old_size + 1);
     }
 
     it "should decrease queue size when dequeue value" {
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
         let old_size = queue.size();
-        queue.dequeue();
+        queue.dequeue().unwrap();
 
         assert_eq!(queue.size(), old_size - 1);
     }
 
     it "should contain value that was enqueued" {
-        queue.enqueue(1);
+        queue.enqueue(1).unwrap();
         assert!(queue.contains(1));
     }
 
     it "should contain values that were enqueued" {
-        queue.enqueue(10);
-        queue.enqueue(20);
-        queue.enqueue(30);
-        queue.enqueue(40);
+        queue.enqueue(10).unwrap();
+        queue.enqueue(20).unwrap();
+        queue.enqueue(30).unwrap();
+        queue.enqueue(40).unwrap();
 
         assert!(queue.contains(10));
         assert!(queue.contains(20));
@@ -55,13 +56,13 @@ describe! unbounded_blocking_queue_test {
     }
 
     it "should dequeue first enqueued value" {
-        queue.enqueue(10);
-        queue.enqueue(20);
-        queue.enqueue(30);
+        queue.enqueue(10).unwrap();
+        queue.enqueue(20).unwrap();
+        queue.enqueue(30).unwrap();
 
-        assert_eq!(queue.dequeue(), 10);
-        assert_eq!(queue.dequeue(), 20);
-        assert_eq!(queue.dequeue(), 30);
+        assert_eq!(queue.dequeue(), Ok(10));
+        assert_eq!(queue.dequeue(), Ok(20));
+        assert_eq!(queue.dequeue(), Ok(30));
     }
 
     it "should insert offered value" {
@@ -70,14 +71,170 @@ describe! unbounded_blocking_queue_test {
     }
 
     it "should peek first element but not delete" {
-        queue.enqueue(1);
-        queue.enqueue(2);
-        queue.enqueue(3);
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.enqueue(3).unwrap();
 
         assert_eq!(queue.peek(), Some(1));
         assert_eq!(queue.peek(), Some(1));
     }
 
+    it "should peek clone of value which stays in queue" {
+        let queue = UnboundedBlockingQueue::new();
+        queue.enqueue(String::from("first")).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("first")));
+        assert_eq!(queue.dequeue(), Ok(String::from("first")));
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue = UnboundedBlockingQueue::new();
+            queue.enqueue(value.clone()).unwrap();
+            queue.enqueue(value.clone()).unwrap();
+            queue.enqueue(value.clone()).unwrap();
+            queue.dequeue().unwrap();
+            assert_eq!(Arc::strong_count(&value), 3);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should queue values which could not be compared" {
+        let queue: UnboundedBlockingQueue<Box<Fn() -> i32 + Send>> = UnboundedBlockingQueue::new();
+        assert!(queue.enqueue(Box::new(|| 1)).is_ok());
+        assert!(queue.enqueue(Box::new(|| 2)).is_ok());
+
+        assert_eq!(queue.dequeue().map(|f| f()), Ok(1));
+        assert_eq!(queue.poll().map(|f| f()), Some(2));
+    }
+
+    it "should always enqueue value when try to enqueue" {
+        assert_eq!(queue.try_enqueue(1), Ok(()));
+        assert!(queue.contains(1));
+    }
+
+    it "should poll first enqueued value" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        assert_eq!(queue.poll(), Some(1));
+        assert_eq!(queue.size(), 1);
+    }
+
+    it "should return none when poll empty queue" {
+        assert_eq!(queue.poll(), None);
+    }
+
+    it "should accept value offered with timeout" {
+        assert_eq!(queue.offer_timeout(1, Duration::from_millis(10)), Ok(()));
+        assert_eq!(queue.dequeue(), Ok(1));
+    }
+
+    it "should return none when poll times out on empty queue" {
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should poll value enqueued by other thread before timeout" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                data.enqueue(1).unwrap();
+            }
+        );
+
+        assert_eq!(arc.poll_timeout(Duration::from_secs(10)), Some(1));
+        assert!(jh.join().is_ok());
+    }
+
+    it "should reject values enqueued into closed queue" {
+        queue.close();
+
+        assert!(queue.is_closed());
+        assert_eq!(queue.enqueue(1), Err(1));
+        assert_eq!(queue.try_enqueue(2), Err(2));
+        assert!(!queue.offer(3));
+        assert!(queue.is_empty());
+    }
+
+    it "should dequeue values left in closed queue" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.close();
+
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.poll(), Some(2));
+        assert_eq!(queue.dequeue(), Err(Closed));
+        assert_eq!(queue.poll_timeout(Duration::from_secs(10)), None);
+    }
+
+    it "should wake up consumers waiting on empty queue when it is closed" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let arc = Arc::new(queue);
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        for jh in results {
+            assert_eq!(jh.join().unwrap(), Err(Closed));
+        }
+    }
+
+    it "should drain up to max values in order" {
+        queue.enqueue_all(vec![1, 2, 3, 4, 5]).unwrap();
+        let mut target = Vec::new();
+
+        assert_eq!(queue.drain_to(&mut target, 3), 3);
+        assert_eq!(target, vec![1, 2, 3]);
+        assert_eq!(queue.size(), 2);
+        assert_eq!(queue.drain_to(&mut target, 10), 2);
+        assert_eq!(target, vec![1, 2, 3, 4, 5]);
+        assert!(queue.is_empty());
+    }
+
+    it "should enqueue all values in order" {
+        assert_eq!(queue.enqueue_all(vec![1, 2, 3]), Ok(()));
+
+        assert_eq!(queue.size(), 3);
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.dequeue(), Ok(2));
+        assert_eq!(queue.dequeue(), Ok(3));
+    }
+
+    it "should return all values enqueued into closed queue" {
+        queue.close();
+
+        assert_eq!(queue.enqueue_all(vec![1, 2]), Err(vec![1, 2]));
+        assert!(queue.is_empty());
+    }
+
+    it "should wake up all consumers once when enqueue all values" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let arc = Arc::new(queue);
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(arc.enqueue_all(vec![1; NUMBER_OF_THREADS]), Ok(()));
+
+        for jh in results {
+            assert_eq!(jh.join().unwrap(), Ok(1));
+        }
+    }
+
     it "should wait when queue is empty" {
         const NUMBER_OF_THREADS: usize = 10;
         let arc = Arc::new(queue);
@@ -87,20 +244,20 @@ describe! unbounded_blocking_queue_test {
             let data = arc.clone();
             let jh = thread::spawn(
                 move || {
-                    assert_eq!(data.dequeue(), 1);
-                    data.enqueue(1);
+                    assert_eq!(data.dequeue(), Ok(1));
+                    data.enqueue(1).unwrap();
                 }
             );
             results.push(jh);
         }
 
-        arc.enqueue(1);
+        arc.enqueue(1).unwrap();
 
         for jh in results {
             assert!(jh.join().is_ok());
         }
 
-        assert_eq!(arc.dequeue(), 1);
+        assert_eq!(arc.dequeue(), Ok(1));
     }
 
     it "should notify threads when offer value to queue" {
@@ -112,7 +269,7 @@ describe! unbounded_blocking_queue_test {
             let data = arc.clone();
             let jh = thread::spawn(
                 move || {
-                    assert_eq!(data.dequeue(), 1);
+                    assert_eq!(data.dequeue(), Ok(1));
                     assert!(data.offer(1));
                 }
             );
@@ -126,6 +283,6 @@ describe! unbounded_blocking_queue_test {
             assert!(jh.join().is_ok());
         }
 
-        assert_eq!(arc.dequeue(), 1);
+        assert_eq!(arc.dequeue(), Ok(1));
     }
 }
@@ -0,0 +1,237 @@
+pub use concrust::queue::{LockFreeArrayQueue, LockFreeBlockingQueue, NonBlockingQueue, BlockingQueue, Closed};
+
+pub use std::sync::Arc;
+pub use std::time::Duration;
+
+pub use std::thread;
+
+pub use test_lock_free_linked_queue::concurrent_peek_and_pop;
+
+describe! lock_free_array_queue_test {
+
+    before_each {
+        const CAPACITY: usize = 16;
+        let queue: LockFreeArrayQueue<i32> = LockFreeArrayQueue::with_capacity(CAPACITY);
+    }
+
+    it "should create empty queue with capacity rounded up to power of two" {
+        let queue: LockFreeArrayQueue<i32> = LockFreeArrayQueue::with_capacity(10);
+
+        assert_eq!(queue.capacity(), 16);
+        assert!(queue.is_empty());
+    }
+
+    it "should pop values in order they were pushed" {
+        assert_eq!(queue.try_push(1), Ok(()));
+        assert_eq!(queue.try_push(2), Ok(()));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue: LockFreeArrayQueue<String> = LockFreeArrayQueue::with_capacity(CAPACITY);
+        queue.try_push(String::from("first")).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("first")));
+        assert_eq!(queue.try_pop(), Some(String::from("first")));
+        assert_eq!(queue.peek(), None);
+    }
+
+    it "should peek while consumers pop values" {
+        let queue: LockFreeArrayQueue<String> = LockFreeArrayQueue::with_capacity(CAPACITY);
+        concurrent_peek_and_pop(Arc::new(queue));
+    }
+
+    it "should reject value pushed into full queue" {
+        for i in 0..CAPACITY as i32 {
+            assert_eq!(queue.try_push(i), Ok(()));
+        }
+
+        assert!(queue.is_full());
+        assert_eq!(queue.try_push(100), Err(100));
+    }
+
+    it "should reuse slots after several laps" {
+        for i in 0..(CAPACITY * 10) as i32 {
+            assert_eq!(queue.try_push(i), Ok(()));
+            assert_eq!(queue.try_pop(), Some(i));
+        }
+
+        assert!(queue.is_empty());
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue = LockFreeArrayQueue::with_capacity(CAPACITY);
+            queue.try_push(value.clone()).unwrap();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_pop().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should move all values between producers and consumers" {
+        const NUMBER_OF_THREADS: i32 = 4;
+        const VALUES_PER_THREAD: i32 = 10000;
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+        let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || {
+                    for i in 0..VALUES_PER_THREAD {
+                        let mut val = t * VALUES_PER_THREAD + i;
+                        while let Err(rejected) = data.try_push(val) {
+                            val = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                }
+            ));
+            let data = arc.clone();
+            consumers.push(thread::spawn(
+                move || {
+                    let mut values = Vec::with_capacity(VALUES_PER_THREAD as usize);
+                    while values.len() < VALUES_PER_THREAD as usize {
+                        match data.try_pop() {
+                            Some(val) => values.push(val),
+                            None => thread::yield_now(),
+                        }
+                    }
+                    values
+                }
+            ));
+        }
+
+        for jh in producers {
+            assert!(jh.join().is_ok());
+        }
+        let mut values = Vec::new();
+        for jh in consumers {
+            values.extend(jh.join().unwrap());
+        }
+        values.sort();
+
+        assert_eq!(values, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).collect::<Vec<i32>>());
+        assert!(arc.is_empty());
+    }
+}
+
+describe! lock_free_blocking_queue_test {
+
+    before_each {
+        const CAPACITY: usize = 16;
+        let queue: LockFreeBlockingQueue<i32> = LockFreeBlockingQueue::with_capacity(CAPACITY);
+    }
+
+    it "should dequeue values in order they were enqueued" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.poll(), Some(2));
+        assert_eq!(queue.poll(), None);
+    }
+
+    it "should not enqueue value into full queue" {
+        for i in 0..CAPACITY as i32 {
+            queue.enqueue(i).unwrap();
+        }
+
+        assert!(!queue.offer(100));
+        assert_eq!(queue.try_enqueue(200), Err(200));
+        assert_eq!(queue.offer_timeout(300, Duration::from_millis(10)), Err(300));
+    }
+
+    it "should return none when poll times out on empty queue" {
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should drain and enqueue many values" {
+        assert_eq!(queue.enqueue_all(vec![1, 2, 3]), Ok(()));
+        let mut target = Vec::new();
+
+        assert_eq!(queue.drain_to(&mut target, 2), 2);
+        assert_eq!(target, vec![1, 2]);
+        assert_eq!(queue.len(), 1);
+    }
+
+    it "should reject values and wake up consumers when queue is closed" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let arc = Arc::new(queue);
+        arc.enqueue(1).unwrap();
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        assert_eq!(arc.dequeue(), Ok(1));
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        for jh in results {
+            assert_eq!(jh.join().unwrap(), Err(Closed));
+        }
+        assert!(arc.is_closed());
+        assert_eq!(arc.enqueue(2), Err(2));
+        assert_eq!(arc.enqueue_all(vec![3, 4]), Err(vec![3, 4]));
+    }
+
+    it "should wait when queue is full" {
+        for i in 0..CAPACITY as i32 {
+            queue.enqueue(i).unwrap();
+        }
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.enqueue(100));
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(arc.dequeue(), Ok(0));
+
+        assert_eq!(jh.join().unwrap(), Ok(()));
+        assert_eq!(arc.len(), CAPACITY);
+    }
+
+    it "should move all values between blocked producers and consumers" {
+        const NUMBER_OF_THREADS: i32 = 4;
+        const VALUES_PER_THREAD: i32 = 10000;
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+        let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || data.enqueue_all((0..VALUES_PER_THREAD).map(|i| t * VALUES_PER_THREAD + i))
+            ));
+            let data = arc.clone();
+            consumers.push(thread::spawn(
+                move || (0..VALUES_PER_THREAD).map(|_| data.dequeue().unwrap()).collect::<Vec<i32>>()
+            ));
+        }
+
+        for jh in producers {
+            assert_eq!(jh.join().unwrap(), Ok(()));
+        }
+        let mut values = Vec::new();
+        for jh in consumers {
+            values.extend(jh.join().unwrap());
+        }
+        values.sort();
+
+        assert_eq!(values, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).collect::<Vec<i32>>());
+    }
+}
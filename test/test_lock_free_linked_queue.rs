@@ -0,0 +1,244 @@
+pub use concrust::queue::{LockFreeLinkedQueue, LockFreeBlockingQueue, NonBlockingQueue, BlockingQueue, Closed};
+pub use concrust::reclaim::Hazards;
+
+pub use std::sync::Arc;
+pub use std::time::Duration;
+pub use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use std::thread;
+
+describe! lock_free_linked_queue_test {
+
+    before_each {
+        let queue: LockFreeLinkedQueue<i32> = LockFreeLinkedQueue::new();
+    }
+
+    it "should create empty queue" {
+        assert!(queue.is_empty());
+        assert!(!queue.is_full());
+        assert_eq!(queue.len(), 0);
+    }
+
+    it "should pop values in order they were pushed" {
+        assert_eq!(queue.try_push(1), Ok(()));
+        assert_eq!(queue.try_push(2), Ok(()));
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+        assert_eq!(queue.try_pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue: LockFreeLinkedQueue<Arc<i32>> = LockFreeLinkedQueue::new();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_pop().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue: LockFreeLinkedQueue<String> = LockFreeLinkedQueue::new();
+        queue.try_push(String::from("first")).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("first")));
+        assert_eq!(queue.try_pop(), Some(String::from("first")));
+        assert_eq!(queue.peek(), None);
+    }
+
+    it "should peek while consumers pop values" {
+        let queue: LockFreeLinkedQueue<String> = LockFreeLinkedQueue::new();
+        concurrent_peek_and_pop(Arc::new(queue));
+    }
+
+    it "should peek while consumers pop values with hazard pointers" {
+        let queue: LockFreeLinkedQueue<String, Hazards> = LockFreeLinkedQueue::new();
+        concurrent_peek_and_pop(Arc::new(queue));
+    }
+
+    it "should move all values between producers and consumers" {
+        concurrent_push_and_pop(Arc::new(queue));
+    }
+
+    it "should move all values between producers and consumers with hazard pointers" {
+        let queue: LockFreeLinkedQueue<i32, Hazards> = LockFreeLinkedQueue::new();
+        concurrent_push_and_pop(Arc::new(queue));
+    }
+}
+
+describe! unbounded_lock_free_blocking_queue_test {
+
+    before_each {
+        let queue: LockFreeBlockingQueue<i32, LockFreeLinkedQueue<i32>> = LockFreeBlockingQueue::unbounded();
+    }
+
+    it "should always enqueue value into open queue" {
+        for i in 0..1000 {
+            assert_eq!(queue.try_enqueue(i), Ok(()));
+        }
+
+        assert_eq!(queue.len(), 1000);
+        assert_eq!(queue.dequeue(), Ok(0));
+    }
+
+    it "should return none when poll times out on empty queue" {
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should wake up consumer waiting on empty queue" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.enqueue(1).unwrap();
+
+        assert_eq!(jh.join().unwrap(), Ok(1));
+    }
+
+    it "should wake up consumers when queue is closed" {
+        const NUMBER_OF_THREADS: usize = 10;
+        let arc = Arc::new(queue);
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            results.push(thread::spawn(move || data.dequeue()));
+        }
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        for jh in results {
+            assert_eq!(jh.join().unwrap(), Err(Closed));
+        }
+        assert_eq!(arc.enqueue(1), Err(1));
+    }
+
+    it "should move all values between producers and blocked consumers" {
+        const NUMBER_OF_THREADS: i32 = 8;
+        const VALUES_PER_THREAD: i32 = 10000;
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+        let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            consumers.push(thread::spawn(
+                move || (0..VALUES_PER_THREAD).map(|_| data.dequeue().unwrap()).collect::<Vec<i32>>()
+            ));
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || for i in 0..VALUES_PER_THREAD { data.enqueue(t * VALUES_PER_THREAD + i).unwrap(); }
+            ));
+        }
+
+        for jh in producers {
+            assert!(jh.join().is_ok());
+        }
+        let mut values = Vec::new();
+        for jh in consumers {
+            values.extend(jh.join().unwrap());
+        }
+        values.sort();
+
+        assert_eq!(values, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).collect::<Vec<i32>>());
+        assert!(arc.is_empty());
+    }
+}
+
+pub fn concurrent_push_and_pop<Q: NonBlockingQueue<i32> + Send + Sync + 'static>(queue: Arc<Q>) {
+    const NUMBER_OF_THREADS: i32 = 8;
+    const VALUES_PER_THREAD: i32 = 10000;
+    let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+    let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+    for t in 0..NUMBER_OF_THREADS {
+        let data = queue.clone();
+        producers.push(thread::spawn(
+            move || for i in 0..VALUES_PER_THREAD { data.try_push(t * VALUES_PER_THREAD + i).unwrap(); }
+        ));
+        let data = queue.clone();
+        consumers.push(thread::spawn(
+            move || {
+                let mut values = Vec::with_capacity(VALUES_PER_THREAD as usize);
+                while values.len() < VALUES_PER_THREAD as usize {
+                    match data.try_pop() {
+                        Some(val) => values.push(val),
+                        None => thread::yield_now(),
+                    }
+                }
+                values
+            }
+        ));
+    }
+
+    for jh in producers {
+        assert!(jh.join().is_ok());
+    }
+    let mut values = Vec::new();
+    for jh in consumers {
+        values.extend(jh.join().unwrap());
+    }
+    values.sort();
+
+    assert_eq!(values, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).collect::<Vec<i32>>());
+    assert!(queue.is_empty());
+}
+
+pub fn concurrent_peek_and_pop<Q: NonBlockingQueue<String> + Send + Sync + 'static>(queue: Arc<Q>) {
+    const NUMBER_OF_THREADS: usize = 4;
+    const VALUES: usize = 10000;
+    let done = Arc::new(AtomicBool::new(false));
+    let mut peekers = Vec::with_capacity(NUMBER_OF_THREADS);
+    let mut consumers = Vec::with_capacity(NUMBER_OF_THREADS);
+
+    for _ in 0..NUMBER_OF_THREADS {
+        let data = queue.clone();
+        let stop = done.clone();
+        peekers.push(thread::spawn(
+            move || while !stop.load(Ordering::SeqCst) {
+                if let Some(val) = data.peek() {
+                    assert!(val.parse::<usize>().unwrap() < VALUES);
+                }
+            }
+        ));
+        let data = queue.clone();
+        consumers.push(thread::spawn(
+            move || {
+                let mut values = Vec::new();
+                while values.len() < VALUES / NUMBER_OF_THREADS {
+                    match data.try_pop() {
+                        Some(val) => values.push(val.parse::<usize>().unwrap()),
+                        None => thread::yield_now(),
+                    }
+                }
+                values
+            }
+        ));
+    }
+    for i in 0..VALUES {
+        while queue.try_push(i.to_string()).is_err() {
+            thread::yield_now();
+        }
+    }
+
+    let mut values = Vec::new();
+    for jh in consumers {
+        values.extend(jh.join().unwrap());
+    }
+    done.store(true, Ordering::SeqCst);
+    for jh in peekers {
+        assert!(jh.join().is_ok());
+    }
+    values.sort();
+
+    assert_eq!(values, (0..VALUES).collect::<Vec<usize>>());
+}
@@ -1,9 +1,55 @@
-pub use concrust::map::ConcurrentHashMap;
+pub use concrust::map::{ConcurrentHashMap, LockFreeHashMap, ConcurrentMap, Entry};
+pub use concrust::epoch;
+pub use concrust::reclaim::Hazards;
+
+pub use std::hash::{BuildHasherDefault, Hasher};
+pub use std::collections::hash_map::RandomState;
+
+pub use std::usize;
+
+pub use std::sync::{Arc, Barrier};
+pub use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub use std::thread;
+pub use std::time::Duration;
+
+pub struct DropCounter {
+    counter: Arc<AtomicUsize>
+}
+
+impl Drop for DropCounter {
+
+    fn drop(&mut self) {
+        self.counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+pub struct ConstantHasher;
+
+impl Hasher for ConstantHasher {
+
+    fn finish(&self) -> u64 {
+        42
+    }
+
+    fn write(&mut self, _bytes: &[u8]) { }
+}
+
+pub fn insert_update_and_remove<M: ConcurrentMap<i32, i32>>(map: M) {
+    assert_eq!(map.insert(1, 1), None);
+    assert_eq!(map.insert(1, 2), Some(1));
+    assert_eq!(map.get(&1), Some(2));
+    assert!(map.contains_key(&1));
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.remove(&1), Some(2));
+    assert!(map.is_empty());
+}
 
 describe! hash_map_tests {
 
     before_each {
-        let mut map = ConcurrentHashMap::new();
+        let map: ConcurrentHashMap<i32, i32> = ConcurrentHashMap::new();
     }
 
     it "should create new empty map" {
@@ -16,11 +62,11 @@ describe! hash_map_tests {
     }
 
     it "should have capacity that is always highest power of two" {
-        let map = ConcurrentHashMap::with_capacity(6);
+        let map: ConcurrentHashMap<i32, i32> = ConcurrentHashMap::with_capacity(6);
         assert_eq!(map.capacity(), 8);
-        let map = ConcurrentHashMap::with_capacity(10);
+        let map: ConcurrentHashMap<i32, i32> = ConcurrentHashMap::with_capacity(10);
         assert_eq!(map.capacity(), 16);
-        let map = ConcurrentHashMap::with_capacity(100);
+        let map: ConcurrentHashMap<i32, i32> = ConcurrentHashMap::with_capacity(100);
         assert_eq!(map.capacity(), 128);
     }
 
@@ -31,22 +77,22 @@ describe! hash_map_tests {
 
     it "should decrease size when remove from map" {
         map.insert(1, 1);
-        map.remove(1);
+        map.remove(&1);
         assert!(map.is_empty());
     }
 
     it "should remove none if there is no such key" {
-        assert_eq!(map.remove(1), None);
+        assert_eq!(map.remove(&1), None);
     }
 
     it "should remove inserted value" {
         map.insert(1, 10);
-        assert_eq!(map.remove(1), Some(10));
+        assert_eq!(map.remove(&1), Some(10));
     }
 
     it "should not remove value that was not inserted into map" {
         map.insert(1, 10);
-        assert_eq!(map.remove(2), None);
+        assert_eq!(map.remove(&2), None);
         assert!(!map.is_empty());
     }
 
@@ -55,8 +101,784 @@ describe! hash_map_tests {
         map.insert(2, 20);
         map.insert(3, 30);
 
-        assert_eq!(map.remove(1), Some(10));
-        assert_eq!(map.remove(2), Some(20));
-        assert_eq!(map.remove(3), Some(30));
+        assert_eq!(map.remove(&1), Some(10));
+        assert_eq!(map.remove(&2), Some(20));
+        assert_eq!(map.remove(&3), Some(30));
+    }
+
+    it "should return previous value when insert existed key" {
+        assert_eq!(map.insert(1, 10), None);
+        assert_eq!(map.insert(1, 20), Some(10));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.remove(&1), Some(20));
+    }
+
+    it "should store owned keys and values" {
+        let map = ConcurrentHashMap::new();
+        map.insert(String::from("one"), vec![1]);
+        map.insert(String::from("two"), vec![2, 2]);
+
+        assert_eq!(map.remove("one"), Some(vec![1]));
+        assert_eq!(map.remove("two"), Some(vec![2, 2]));
+        assert_eq!(map.remove("three"), None);
+    }
+
+    it "should keep all values when there are more keys than buckets" {
+        for i in 0..100 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.len(), 100);
+        for i in 0..100 {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert!(map.is_empty());
+    }
+
+    it "should drop replaced and remaining values" {
+        let counter = Arc::new(AtomicUsize::new(0));
+        {
+            let map = ConcurrentHashMap::new();
+            map.insert(1, DropCounter { counter: counter.clone() });
+            map.insert(1, DropCounter { counter: counter.clone() });
+            assert_eq!(counter.load(Ordering::Relaxed), 1);
+            map.insert(2, DropCounter { counter: counter.clone() });
+        }
+        assert_eq!(counter.load(Ordering::Relaxed), 3);
+    }
+
+    it "should create a map with specified hasher" {
+        let map = ConcurrentHashMap::with_hasher(RandomState::new());
+        map.insert(1, 10);
+        assert_eq!(map.capacity(), 16);
+        assert_eq!(map.remove(&1), Some(10));
+    }
+
+    it "should create a map with specified capacity and hasher" {
+        let map: ConcurrentHashMap<i32, i32, RandomState> = ConcurrentHashMap::with_capacity_and_hasher(100, RandomState::new());
+        assert_eq!(map.capacity(), 128);
+    }
+
+    it "should keep all values when every key collides" {
+        let map = ConcurrentHashMap::with_hasher(BuildHasherDefault::<ConstantHasher>::default());
+        for i in 0..10 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.len(), 10);
+        for i in 0..10 {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert!(map.is_empty());
+    }
+
+    it "should grow when number of entries exceeds load factor" {
+        for i in 0..12 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.capacity(), 16);
+
+        map.insert(12, 12);
+        assert_eq!(map.capacity(), 32);
+    }
+
+    it "should grow according to specified load factor" {
+        let map = ConcurrentHashMap::with_capacity_and_load_factor(16, 0.5);
+        for i in 0..8 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.capacity(), 16);
+        assert_eq!(map.load_factor(), 0.5);
+
+        map.insert(8, 8);
+        assert_eq!(map.capacity(), 32);
+    }
+
+    it "should keep all values after several resizes" {
+        for i in 0..1000 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.len(), 1000);
+        assert!(map.capacity() >= 1024);
+        for i in 0..1000 {
+            assert_eq!(map.remove(&i), Some(i * 10));
+        }
+        assert!(map.is_empty());
+    }
+
+    failing "should not accept non positive load factor" {
+        let map: ConcurrentHashMap<i32, i32> = ConcurrentHashMap::with_capacity_and_load_factor(16, 0.0);
+    }
+
+    it "should get copy of inserted value" {
+        map.insert(1, 10);
+        assert_eq!(map.get(&1), Some(10));
+        assert_eq!(map.get(&2), None);
+    }
+
+    it "should get guard of inserted value" {
+        let map = ConcurrentHashMap::new();
+        map.insert(1, String::from("one"));
+        {
+            let value = map.get_ref(&1).unwrap();
+            assert_eq!(&*value, "one");
+        }
+        assert!(map.get_ref(&2).is_none());
+    }
+
+    it "should contain inserted key" {
+        map.insert(1, 10);
+        assert!(map.contains_key(&1));
+        assert!(!map.contains_key(&2));
+    }
+
+    it "should insert computed value only if key is absent" {
+        assert_eq!(map.get_or_insert_with(1, || 10), 10);
+        assert_eq!(map.get_or_insert_with(1, || panic!("value is already in map")), 10);
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should compute value from current one" {
+        assert_eq!(map.compute(1, |_, value| Some(value.map_or(1, |v| v + 1))), Some(1));
+        assert_eq!(map.compute(1, |_, value| Some(value.map_or(1, |v| v + 1))), Some(2));
+        assert_eq!(map.get(&1), Some(2));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should remove key when computed value is none" {
+        map.insert(1, 10);
+        assert_eq!(map.compute(1, |_, _| None), None);
+        assert!(!map.contains_key(&1));
+        assert!(map.is_empty());
+    }
+
+    it "should merge value with current one" {
+        assert_eq!(map.merge(1, 1, |old, new| old + new), 1);
+        assert_eq!(map.merge(1, 5, |old, new| old + new), 6);
+        assert_eq!(map.get(&1), Some(6));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should insert value into vacant entry" {
+        *map.entry(1).or_insert(0) += 10;
+        assert_eq!(map.get(&1), Some(10));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should not insert value into occupied entry" {
+        map.insert(1, 10);
+        *map.entry(1).or_insert_with(|| panic!("entry is occupied")) += 1;
+        assert_eq!(map.get(&1), Some(11));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should modify only occupied entry" {
+        map.entry(1).and_modify(|value| *value += 1).or_insert(1);
+        assert_eq!(map.get(&1), Some(1));
+        map.entry(1).and_modify(|value| *value += 1).or_insert(1);
+        assert_eq!(map.get(&1), Some(2));
+    }
+
+    it "should remove occupied entry" {
+        map.insert(1, 10);
+        match map.entry(1) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove(), 10),
+            Entry::Vacant(_) => panic!("entry should be occupied"),
+        }
+        assert!(!map.contains_key(&1));
+        assert!(map.is_empty());
+    }
+
+    it "should return key of entry" {
+        map.insert(1, 10);
+        assert_eq!(*map.entry(1).key(), 1);
+        assert_eq!(*map.entry(2).key(), 2);
+    }
+
+    it "should grow when values are inserted through entries" {
+        for i in 0..13 {
+            map.entry(i).or_insert(i);
+        }
+        assert_eq!(map.capacity(), 32);
+        assert_eq!(map.len(), 13);
+    }
+
+    it "should iterate over all entries" {
+        for i in 0..100 {
+            map.insert(i, i * 10);
+        }
+        let mut entries = map.iter().collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, (0..100).map(|i| (i, i * 10)).collect::<Vec<_>>());
+    }
+
+    it "should iterate over keys and values" {
+        for i in 0..100 {
+            map.insert(i, i * 10);
+        }
+        let mut keys = map.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, (0..100).collect::<Vec<_>>());
+
+        let mut values = map.values().collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, (0..100).map(|i| i * 10).collect::<Vec<_>>());
+    }
+
+    it "should remove all entries when drained" {
+        for i in 0..100 {
+            map.insert(i, i * 10);
+        }
+        let mut entries = map.drain().collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, (0..100).map(|i| (i, i * 10)).collect::<Vec<_>>());
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+    }
+
+    it "should remove all entries when drain is dropped" {
+        for i in 0..100 {
+            map.insert(i, i * 10);
+        }
+        map.drain().next();
+        assert!(map.is_empty());
+    }
+
+    it "should apply function to each entry" {
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        let sequential = AtomicUsize::new(0);
+        map.for_each(usize::MAX, |_, &value| { sequential.fetch_add(value as usize, Ordering::Relaxed); });
+        let parallel = AtomicUsize::new(0);
+        map.for_each(1, |_, &value| { parallel.fetch_add(value as usize, Ordering::Relaxed); });
+
+        assert_eq!(sequential.load(Ordering::Relaxed), 999 * 1000 / 2);
+        assert_eq!(parallel.load(Ordering::Relaxed), 999 * 1000 / 2);
+    }
+
+    it "should reduce entries" {
+        assert_eq!(map.reduce(1, |_, &value| value, |left, right| left + right), None);
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.reduce(usize::MAX, |_, &value| value, |left, right| left + right), Some(999 * 1000 / 2));
+        assert_eq!(map.reduce(1, |_, &value| value, |left, right| left + right), Some(999 * 1000 / 2));
+    }
+
+    it "should search for entry" {
+        for i in 0..1000 {
+            map.insert(i, i * 10);
+        }
+        assert_eq!(map.search(1, |&key, &value| if key == 500 { Some(value) } else { None }), Some(5000));
+        assert_eq!(map.search(1, |&key, _| if key == 5000 { Some(key) } else { None }), None);
+    }
+
+    it "should retain only entries matching predicate" {
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+        map.retain(1,
+            |&key, value| {
+                *value *= 10;
+                key % 2 == 0
+            }
+        );
+        assert_eq!(map.len(), 500);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), if i % 2 == 0 { Some(i * 10) } else { None });
+        }
+    }
+}
+
+describe! concurrent_hash_map_tests {
+
+    before_each {
+        const NUMBER_OF_THREADS: usize = 8;
+        const OPERATIONS_PER_THREAD: usize = 1000;
+        let map: Arc<ConcurrentHashMap<usize, usize>> = Arc::new(ConcurrentHashMap::new());
+        let barrier = Arc::new(Barrier::new(NUMBER_OF_THREADS));
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+    }
+
+    it "should insert values from many threads" {
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        assert_eq!(map.insert(key, key), None);
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), NUMBER_OF_THREADS * OPERATIONS_PER_THREAD);
+        for key in 0..NUMBER_OF_THREADS * OPERATIONS_PER_THREAD {
+            assert_eq!(map.get(&key), Some(key));
+        }
+    }
+
+    it "should remove values from many threads" {
+        for key in 0..NUMBER_OF_THREADS * OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        assert_eq!(map.remove(&key), Some(key));
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert!(map.is_empty());
+    }
+
+    it "should atomically merge values of the same keys from many threads" {
+        const NUMBER_OF_KEYS: usize = 10;
+        for _ in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        map.merge(i % NUMBER_OF_KEYS, 1, |old, new| old + new);
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), NUMBER_OF_KEYS);
+        for key in 0..NUMBER_OF_KEYS {
+            assert_eq!(map.get(&key), Some(NUMBER_OF_THREADS * OPERATIONS_PER_THREAD / NUMBER_OF_KEYS));
+        }
+    }
+
+    it "should read inserted values while table is resizing" {
+        for key in 0..OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        if id % 2 == 0 {
+                            let key = (id + 1) * OPERATIONS_PER_THREAD + i;
+                            map.insert(key, key);
+                        }
+                        else {
+                            assert_eq!(map.get(&i), Some(i));
+                        }
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), (NUMBER_OF_THREADS / 2 + 1) * OPERATIONS_PER_THREAD);
+    }
+
+    it "should atomically update values through entries from many threads" {
+        const NUMBER_OF_KEYS: usize = 10;
+        for _ in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        *map.entry(i % NUMBER_OF_KEYS).or_insert(0) += 1;
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), NUMBER_OF_KEYS);
+        for key in 0..NUMBER_OF_KEYS {
+            assert_eq!(map.get(&key), Some(NUMBER_OF_THREADS * OPERATIONS_PER_THREAD / NUMBER_OF_KEYS));
+        }
+    }
+
+    it "should iterate over entries while table is resizing" {
+        for key in 0..OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    if id % 2 == 0 {
+                        for i in 0..OPERATIONS_PER_THREAD {
+                            let key = (id + 1) * OPERATIONS_PER_THREAD + i;
+                            map.insert(key, key);
+                        }
+                    }
+                    else {
+                        let mut keys = map.keys().filter(|&key| key < OPERATIONS_PER_THREAD).collect::<Vec<_>>();
+                        keys.sort();
+                        assert_eq!(keys, (0..OPERATIONS_PER_THREAD).collect::<Vec<_>>());
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+    }
+}
+
+describe! concurrent_map_tests {
+
+    it "should use concurrent hash map through concurrent map trait" {
+        insert_update_and_remove(ConcurrentHashMap::new());
+    }
+
+    it "should use lock free hash map through concurrent map trait" {
+        insert_update_and_remove(LockFreeHashMap::new());
+    }
+
+    it "should use lock free hash map with hazard pointers through concurrent map trait" {
+        insert_update_and_remove(LockFreeHashMap::<_, _, RandomState, Hazards>::default());
+    }
+}
+
+describe! lock_free_hash_map_tests {
+
+    before_each {
+        let map: LockFreeHashMap<i32, i32> = LockFreeHashMap::new();
+    }
+
+    it "should create new empty map" {
+        assert!(map.is_empty());
+        assert_eq!(map.len(), 0);
+    }
+
+    it "should have capacity that is always highest power of two" {
+        assert_eq!(map.capacity(), 16);
+        let map: LockFreeHashMap<i32, i32> = LockFreeHashMap::with_capacity(10);
+        assert_eq!(map.capacity(), 16);
+        let map: LockFreeHashMap<i32, i32> = LockFreeHashMap::with_capacity(100);
+        assert_eq!(map.capacity(), 128);
+    }
+
+    it "should get inserted values" {
+        for i in 0..100 {
+            assert_eq!(map.insert(i, i * 10), None);
+        }
+
+        assert_eq!(map.len(), 100);
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(i * 10));
+        }
+        assert_eq!(map.get(&100), None);
+    }
+
+    it "should update value of existing key" {
+        map.insert(1, 1);
+
+        assert_eq!(map.insert(1, 2), Some(1));
+        assert_eq!(map.get(&1), Some(2));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should remove key from map" {
+        map.insert(1, 1);
+        map.insert(2, 2);
+
+        assert_eq!(map.remove(&1), Some(1));
+        assert_eq!(map.remove(&1), None);
+        assert!(!map.contains_key(&1));
+        assert!(map.contains_key(&2));
+        assert_eq!(map.len(), 1);
+    }
+
+    it "should insert removed key again" {
+        map.insert(1, 1);
+        map.remove(&1);
+
+        assert_eq!(map.insert(1, 2), None);
+        assert_eq!(map.get(&1), Some(2));
+    }
+
+    it "should grow and keep all values" {
+        for i in 0..1000 {
+            map.insert(i, i);
+        }
+
+        assert!(map.capacity() > 16);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), Some(i));
+        }
+    }
+
+    it "should keep values of colliding keys" {
+        let map: LockFreeHashMap<i32, i32, BuildHasherDefault<ConstantHasher>> = LockFreeHashMap::default();
+        for i in 0..10 {
+            map.insert(i, i);
+        }
+        map.remove(&5);
+
+        for i in 0..10 {
+            assert_eq!(map.get(&i), if i == 5 { None } else { Some(i) });
+        }
+    }
+
+    it "should drop all values" {
+        let counter = Arc::new(AtomicUsize::new(0));
+        {
+            let map = LockFreeHashMap::new();
+            for i in 0..10 {
+                map.insert(i, Arc::new(DropCounter { counter: counter.clone() }));
+            }
+            map.insert(0, Arc::new(DropCounter { counter: counter.clone() }));
+            map.remove(&1);
+        }
+
+        // replaced and removed values are destroyed once global epoch advances
+        for _ in 0..1000 {
+            if counter.load(Ordering::Relaxed) == 11 {
+                break;
+            }
+            epoch::pin().flush();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(counter.load(Ordering::Relaxed), 11);
+    }
+}
+
+describe! concurrent_lock_free_hash_map_tests {
+
+    before_each {
+        const NUMBER_OF_THREADS: usize = 8;
+        const OPERATIONS_PER_THREAD: usize = 1000;
+        let map: Arc<LockFreeHashMap<usize, usize>> = Arc::new(LockFreeHashMap::new());
+        let barrier = Arc::new(Barrier::new(NUMBER_OF_THREADS));
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+    }
+
+    it "should insert values from many threads" {
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        map.insert(key, key);
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), NUMBER_OF_THREADS * OPERATIONS_PER_THREAD);
+        for key in 0..NUMBER_OF_THREADS * OPERATIONS_PER_THREAD {
+            assert_eq!(map.get(&key), Some(key));
+        }
+    }
+
+    it "should remove values from many threads" {
+        for key in 0..NUMBER_OF_THREADS * OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        assert_eq!(map.remove(&key), Some(key));
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert!(map.is_empty());
+    }
+
+    it "should remove each key only once when threads race" {
+        for key in 0..OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+        let removed = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let removed = removed.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for key in 0..OPERATIONS_PER_THREAD {
+                        if map.remove(&key).is_some() {
+                            removed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(removed.load(Ordering::Relaxed), OPERATIONS_PER_THREAD);
+        assert!(map.is_empty());
+    }
+
+    it "should read inserted values while table is growing" {
+        for key in 0..OPERATIONS_PER_THREAD {
+            map.insert(key, key);
+        }
+
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    if id % 2 == 0 {
+                        for i in 0..OPERATIONS_PER_THREAD {
+                            let key = (id + 1) * OPERATIONS_PER_THREAD + i;
+                            map.insert(key, key);
+                        }
+                    }
+                    else {
+                        for key in 0..OPERATIONS_PER_THREAD {
+                            assert_eq!(map.get(&key), Some(key));
+                        }
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+    }
+}
+
+describe! hazard_lock_free_hash_map_tests {
+
+    before_each {
+        const NUMBER_OF_THREADS: usize = 8;
+        const OPERATIONS_PER_THREAD: usize = 1000;
+        let map: Arc<LockFreeHashMap<usize, usize, RandomState, Hazards>> = Arc::new(LockFreeHashMap::default());
+        let barrier = Arc::new(Barrier::new(NUMBER_OF_THREADS));
+        let mut results = Vec::with_capacity(NUMBER_OF_THREADS);
+    }
+
+    it "should insert, read and remove values from many threads" {
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        map.insert(key, key);
+                        assert_eq!(map.get(&key), Some(key));
+                    }
+                    for i in 0..OPERATIONS_PER_THREAD / 2 {
+                        let key = id * OPERATIONS_PER_THREAD + i;
+                        assert_eq!(map.remove(&key), Some(key));
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert_eq!(map.len(), NUMBER_OF_THREADS * OPERATIONS_PER_THREAD / 2);
+    }
+
+    it "should replace values of the same keys from many threads" {
+        const NUMBER_OF_KEYS: usize = 10;
+        for id in 0..NUMBER_OF_THREADS {
+            let map = map.clone();
+            let barrier = barrier.clone();
+            let jh = thread::spawn(
+                move || {
+                    barrier.wait();
+                    for i in 0..OPERATIONS_PER_THREAD {
+                        map.insert(i % NUMBER_OF_KEYS, id);
+                        if let Some(value) = map.get(&(i % NUMBER_OF_KEYS)) {
+                            assert!(value < NUMBER_OF_THREADS);
+                        }
+                        if i % 3 == 0 {
+                            map.remove(&(i % NUMBER_OF_KEYS));
+                        }
+                    }
+                }
+            );
+            results.push(jh);
+        }
+
+        for jh in results {
+            assert!(jh.join().is_ok());
+        }
+
+        assert!(map.len() <= NUMBER_OF_KEYS);
     }
 }
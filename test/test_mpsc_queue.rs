@@ -0,0 +1,234 @@
+pub use concrust::queue::{MpscQueue, MpscLinkedQueue, NonBlockingQueue, BlockingQueue, Closed};
+pub use concrust::queue::{IntrusiveMpscQueue, MpscIntrusiveQueue, MpscLink, Linked};
+
+pub use std::sync::Arc;
+pub use std::time::Duration;
+
+pub use std::thread;
+
+describe! mpsc_queue_test {
+
+    before_each {
+        let queue: MpscQueue<i32> = MpscQueue::mpsc();
+    }
+
+    it "should dequeue values in order they were enqueued" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.poll(), Some(2));
+        assert_eq!(queue.poll(), None);
+        assert!(queue.is_empty());
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue: MpscQueue<String> = MpscQueue::mpsc();
+        queue.enqueue(String::from("first")).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("first")));
+        assert_eq!(queue.dequeue(), Ok(String::from("first")));
+        assert_eq!(queue.peek(), None);
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue = MpscLinkedQueue::new();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_pop().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should return none when poll times out on empty queue" {
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should reject values enqueued into closed queue" {
+        queue.enqueue(1).unwrap();
+        queue.close();
+
+        assert_eq!(queue.enqueue(2), Err(2));
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.dequeue(), Err(Closed));
+    }
+
+    failing "should panic when two threads dequeue at the same time" {
+        let queue: MpscQueue<SlowClone> = MpscQueue::mpsc();
+        queue.enqueue(SlowClone).unwrap();
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        thread::spawn(move || data.peek());
+
+        thread::sleep(Duration::from_millis(50));
+        arc.poll();
+    }
+
+    it "should keep order of every producer" {
+        const NUMBER_OF_THREADS: i32 = 8;
+        const VALUES_PER_THREAD: i32 = 10000;
+        let queue: MpscQueue<(i32, i32)> = MpscQueue::mpsc();
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || for i in 0..VALUES_PER_THREAD { data.enqueue((t, i)).unwrap(); }
+            ));
+        }
+
+        let mut last = vec![-1; NUMBER_OF_THREADS as usize];
+        for _ in 0..NUMBER_OF_THREADS * VALUES_PER_THREAD {
+            let (t, i) = arc.dequeue().unwrap();
+            assert_eq!(last[t as usize] + 1, i);
+            last[t as usize] = i;
+        }
+        for jh in producers {
+            assert!(jh.join().is_ok());
+        }
+        assert!(arc.is_empty());
+    }
+}
+
+/// Value which holds consumer role of queue while it is cloned in peek
+#[derive(Debug)]
+pub struct SlowClone;
+
+impl Clone for SlowClone {
+
+    fn clone(&self) -> SlowClone {
+        thread::sleep(Duration::from_millis(200));
+        SlowClone
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct Message {
+    link: MpscLink,
+    producer: i32,
+    value: i32,
+    payload: String
+}
+
+impl Message {
+
+    pub fn new(producer: i32, value: i32) -> Box<Message> {
+        Box::new(Message {
+            link: MpscLink::new(),
+            producer: producer,
+            value: value,
+            payload: value.to_string()
+        })
+    }
+}
+
+unsafe impl Linked for Message {
+
+    fn link(&self) -> &MpscLink {
+        &self.link
+    }
+}
+
+describe! mpsc_intrusive_queue_test {
+
+    before_each {
+        let queue: MpscIntrusiveQueue<Message> = MpscIntrusiveQueue::new();
+    }
+
+    it "should pop values in order they were pushed" {
+        queue.try_push(Message::new(0, 1)).unwrap();
+        queue.try_push(Message::new(0, 2)).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek().map(|msg| msg.payload), Some(String::from("1")));
+        assert_eq!(queue.try_pop().map(|msg| msg.value), Some(1));
+        assert_eq!(queue.try_pop().map(|msg| msg.value), Some(2));
+        assert!(queue.try_pop().is_none());
+        assert!(queue.peek().is_none());
+        assert!(queue.is_empty());
+    }
+
+    it "should push popped box again" {
+        queue.try_push(Message::new(0, 1)).unwrap();
+        let mut msg = queue.try_pop().unwrap();
+        let address = &*msg as *const Message;
+        msg.value = 2;
+        queue.try_push(msg).unwrap();
+        let msg = queue.try_pop().unwrap();
+
+        assert_eq!(&*msg as *const Message, address);
+        assert_eq!(msg.value, 2);
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue: MpscIntrusiveQueue<Shared> = MpscIntrusiveQueue::new();
+            queue.try_push(Shared::new(value.clone())).unwrap();
+            queue.try_push(Shared::new(value.clone())).unwrap();
+            queue.try_pop().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should keep order of every producer in blocking queue" {
+        const NUMBER_OF_THREADS: i32 = 8;
+        const VALUES_PER_THREAD: i32 = 10000;
+        let queue: IntrusiveMpscQueue<Message> = IntrusiveMpscQueue::mpsc_intrusive();
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || for i in 0..VALUES_PER_THREAD { data.enqueue(Message::new(t, i)).unwrap(); }
+            ));
+        }
+
+        let mut last = vec![-1; NUMBER_OF_THREADS as usize];
+        for _ in 0..NUMBER_OF_THREADS * VALUES_PER_THREAD {
+            let msg = arc.dequeue().unwrap();
+            assert_eq!(last[msg.producer as usize] + 1, msg.value);
+            assert_eq!(msg.payload, msg.value.to_string());
+            last[msg.producer as usize] = msg.value;
+        }
+        for jh in producers {
+            assert!(jh.join().is_ok());
+        }
+        assert!(arc.is_empty());
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Shared {
+    link: MpscLink,
+    value: Arc<i32>
+}
+
+impl Shared {
+
+    pub fn new(value: Arc<i32>) -> Box<Shared> {
+        Box::new(Shared {
+            link: MpscLink::new(),
+            value: value
+        })
+    }
+}
+
+unsafe impl Linked for Shared {
+
+    fn link(&self) -> &MpscLink {
+        &self.link
+    }
+}
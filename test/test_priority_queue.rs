@@ -0,0 +1,131 @@
+pub use concrust::queue::{PriorityBlockingQueue, BlockingQueue, Closed};
+
+pub use std::cmp::Ordering;
+pub use std::sync::Arc;
+pub use std::time::Duration;
+
+pub use std::thread;
+
+describe! priority_blocking_queue_test {
+
+    before_each {
+        let queue: PriorityBlockingQueue<i32> = PriorityBlockingQueue::new();
+    }
+
+    it "should create empty unbounded queue" {
+        assert!(queue.is_empty());
+        assert_eq!(queue.capacity(), None);
+    }
+
+    it "should dequeue value with the highest priority first" {
+        for val in &[5, 1, 8, 3, 9, 2, 7] {
+            queue.enqueue(*val).unwrap();
+        }
+
+        assert_eq!(queue.len(), 7);
+        assert_eq!(queue.peek(), Some(9));
+        assert_eq!(queue.dequeue(), Ok(9));
+        assert_eq!(queue.dequeue(), Ok(8));
+        assert_eq!(queue.poll(), Some(7));
+        assert_eq!(queue.len(), 4);
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue = PriorityBlockingQueue::new();
+        queue.enqueue_all(vec![String::from("a"), String::from("c"), String::from("b")]).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("c")));
+        assert_eq!(queue.dequeue(), Ok(String::from("c")));
+        assert_eq!(queue.peek(), Some(String::from("b")));
+        assert_eq!(queue.len(), 2);
+    }
+
+    it "should order values by comparator" {
+        let queue = PriorityBlockingQueue::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        queue.enqueue_all(vec![5, 1, 8, 3]).unwrap();
+
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.dequeue(), Ok(3));
+        assert_eq!(queue.dequeue(), Ok(5));
+        assert_eq!(queue.dequeue(), Ok(8));
+    }
+
+    it "should drain values in priority order" {
+        queue.enqueue_all(vec![4, 10, 6, 2, 8]).unwrap();
+        let mut target = Vec::new();
+
+        assert_eq!(queue.drain_to(&mut target, 3), 3);
+        assert_eq!(target, vec![10, 8, 6]);
+        assert_eq!(queue.len(), 2);
+    }
+
+    it "should not enqueue value into full bounded queue" {
+        let queue = PriorityBlockingQueue::with_capacity(2);
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        assert_eq!(queue.capacity(), Some(2));
+        assert!(!queue.offer(3));
+        assert_eq!(queue.try_enqueue(4), Err(4));
+        assert_eq!(queue.offer_timeout(5, Duration::from_millis(10)), Err(5));
+    }
+
+    it "should wait for free space in full bounded queue" {
+        let queue = Arc::new(PriorityBlockingQueue::with_capacity_and_comparator(1, |a: &i32, b: &i32| b.cmp(a)));
+        queue.enqueue(1).unwrap();
+        let data = queue.clone();
+        let jh = thread::spawn(move || data.enqueue(2));
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(queue.dequeue(), Ok(1));
+
+        assert_eq!(jh.join().unwrap(), Ok(()));
+        assert_eq!(queue.dequeue(), Ok(2));
+    }
+
+    it "should return none when poll times out on empty queue" {
+        assert_eq!(queue.poll_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should wake up consumer waiting on empty queue" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.enqueue(1).unwrap();
+
+        assert_eq!(jh.join().unwrap(), Ok(1));
+    }
+
+    it "should dequeue values left in closed queue" {
+        queue.enqueue_all(vec![1, 2]).unwrap();
+        queue.close();
+
+        assert_eq!(queue.enqueue(3), Err(3));
+        assert_eq!(queue.dequeue(), Ok(2));
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.dequeue(), Err(Closed));
+    }
+
+    it "should dequeue all values enqueued by several threads in priority order" {
+        const NUMBER_OF_THREADS: i32 = 4;
+        const VALUES_PER_THREAD: i32 = 1000;
+        let arc = Arc::new(queue);
+        let mut producers = Vec::with_capacity(NUMBER_OF_THREADS as usize);
+
+        for t in 0..NUMBER_OF_THREADS {
+            let data = arc.clone();
+            producers.push(thread::spawn(
+                move || for i in 0..VALUES_PER_THREAD { data.enqueue(i * NUMBER_OF_THREADS + t).unwrap(); }
+            ));
+        }
+        for jh in producers {
+            assert!(jh.join().is_ok());
+        }
+
+        let mut target = Vec::new();
+        arc.drain_to(&mut target, usize::max_value());
+        assert_eq!(target, (0..NUMBER_OF_THREADS * VALUES_PER_THREAD).rev().collect::<Vec<i32>>());
+    }
+}
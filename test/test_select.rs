@@ -0,0 +1,150 @@
+pub use concrust::queue::{ArrayBlockingQueue, UnboundedBlockingQueue, BlockingQueue, Select, channel};
+
+pub use std::sync::Arc;
+pub use std::time::Duration;
+
+pub use std::thread;
+
+describe! select_test {
+
+    before_each {
+        let first: ArrayBlockingQueue<i32> = ArrayBlockingQueue::with_capacity(16);
+        let second: UnboundedBlockingQueue<i32> = UnboundedBlockingQueue::new();
+    }
+
+    it "should select queue which is not empty" {
+        second.enqueue(1).unwrap();
+        let mut select = Select::new();
+        let a = select.recv(&first);
+        let b = select.recv(&second);
+
+        assert!(a != b);
+        assert_eq!(select.ready(), b);
+        assert_eq!(second.poll(), Some(1));
+    }
+
+    it "should not select when all queues are empty" {
+        let mut select = Select::new();
+        select.recv(&first);
+        select.recv(&second);
+
+        assert_eq!(select.try_ready(), None);
+        assert_eq!(select.ready_timeout(Duration::from_millis(10)), None);
+    }
+
+    it "should select queue which is not full to send" {
+        for i in 0..16 {
+            first.enqueue(i).unwrap();
+        }
+        let mut select = Select::new();
+        let a = select.send(&first);
+        let b = select.send(&second);
+
+        assert_eq!(select.ready(), b);
+        first.dequeue().unwrap();
+        assert_eq!(select.ready(), a);
+    }
+
+    it "should not starve queues when several are ready" {
+        first.enqueue(1).unwrap();
+        second.enqueue(2).unwrap();
+        let mut select = Select::new();
+        let a = select.recv(&first);
+        let b = select.recv(&second);
+
+        assert_eq!(select.ready(), a);
+        assert_eq!(select.ready(), b);
+        assert_eq!(select.ready(), a);
+    }
+
+    it "should select closed queue" {
+        first.close();
+        let mut select = Select::new();
+        let a = select.recv(&first);
+
+        assert_eq!(select.try_ready(), Some(a));
+    }
+
+    it "should wake up when other thread enqueues value" {
+        let first = Arc::new(first);
+        let second = Arc::new(second);
+        let data = second.clone();
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                data.enqueue(1).unwrap();
+            }
+        );
+
+        let mut select = Select::new();
+        select.recv(&*first);
+        let b = select.recv(&*second);
+
+        assert_eq!(select.ready_timeout(Duration::from_secs(10)), Some(b));
+        assert_eq!(second.poll(), Some(1));
+        assert!(jh.join().is_ok());
+    }
+
+    it "should wake up when other thread frees space in queue" {
+        for i in 0..16 {
+            first.enqueue(i).unwrap();
+        }
+        let data = first.clone();
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                data.dequeue().unwrap();
+            }
+        );
+
+        let mut select = Select::new();
+        let a = select.send(&first);
+
+        assert_eq!(select.ready_timeout(Duration::from_secs(10)), Some(a));
+        assert!(jh.join().is_ok());
+    }
+
+    it "should wake up when all senders of channel are dropped" {
+        let (sender, receiver) = channel::<i32>(16);
+        let jh = thread::spawn(
+            move || {
+                thread::sleep(Duration::from_millis(50));
+                drop(sender);
+            }
+        );
+
+        let mut select = Select::new();
+        select.recv(&first);
+        let r = select.recv(&receiver);
+
+        assert_eq!(select.ready(), r);
+        assert!(receiver.is_disconnected());
+        assert!(jh.join().is_ok());
+    }
+
+    it "should receive all values from several queues" {
+        const VALUES: i32 = 1000;
+        let first = Arc::new(first);
+        let second = Arc::new(second);
+        let (a_data, b_data) = (first.clone(), second.clone());
+        let a_jh = thread::spawn(move || for i in 0..VALUES { a_data.enqueue(i).unwrap(); });
+        let b_jh = thread::spawn(move || for i in 0..VALUES { b_data.enqueue(i).unwrap(); });
+
+        let mut select = Select::new();
+        let a = select.recv(&*first);
+        select.recv(&*second);
+        let mut received = 0;
+        while received < 2 * VALUES {
+            let index = select.ready();
+            let value = if index == a { first.poll() } else { second.poll() };
+            if value.is_some() {
+                received += 1;
+            }
+        }
+
+        assert!(a_jh.join().is_ok());
+        assert!(b_jh.join().is_ok());
+        assert!(first.is_empty());
+        assert!(second.is_empty());
+    }
+}
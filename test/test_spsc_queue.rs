@@ -0,0 +1,97 @@
+pub use concrust::queue::{SpscQueue, SpscArrayQueue, NonBlockingQueue, BlockingQueue, Closed};
+
+pub use std::sync::Arc;
+pub use std::time::Duration;
+
+pub use std::thread;
+
+describe! spsc_queue_test {
+
+    before_each {
+        const CAPACITY: usize = 16;
+        let queue: SpscQueue<i32> = SpscQueue::spsc(CAPACITY);
+    }
+
+    it "should create empty queue with capacity rounded up to power of two" {
+        let queue: SpscArrayQueue<i32> = SpscArrayQueue::with_capacity(10);
+
+        assert_eq!(queue.capacity(), 16);
+        assert!(queue.is_empty());
+    }
+
+    it "should dequeue values in order they were enqueued" {
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.peek(), Some(1));
+        assert_eq!(queue.dequeue(), Ok(1));
+        assert_eq!(queue.poll(), Some(2));
+        assert_eq!(queue.poll(), None);
+    }
+
+    it "should peek clone of value which stays in queue" {
+        let queue: SpscQueue<String> = SpscQueue::spsc(CAPACITY);
+        queue.enqueue(String::from("first")).unwrap();
+
+        assert_eq!(queue.peek(), Some(String::from("first")));
+        assert_eq!(queue.dequeue(), Ok(String::from("first")));
+        assert_eq!(queue.peek(), None);
+    }
+
+    it "should not enqueue value into full queue" {
+        for i in 0..CAPACITY as i32 {
+            queue.enqueue(i).unwrap();
+        }
+
+        assert_eq!(queue.try_enqueue(100), Err(100));
+        assert_eq!(queue.offer_timeout(200, Duration::from_millis(10)), Err(200));
+    }
+
+    it "should reuse slots after several laps" {
+        let queue: SpscArrayQueue<i32> = SpscArrayQueue::with_capacity(CAPACITY);
+        for i in 0..(CAPACITY * 10) as i32 {
+            assert_eq!(queue.try_push(i), Ok(()));
+            assert_eq!(queue.try_pop(), Some(i));
+        }
+
+        assert!(queue.is_empty());
+    }
+
+    it "should drop values left in queue when it is dropped" {
+        let value = Arc::new(1);
+        {
+            let queue = SpscArrayQueue::with_capacity(CAPACITY);
+            queue.try_push(value.clone()).unwrap();
+            queue.try_push(value.clone()).unwrap();
+            queue.try_pop().unwrap();
+            assert_eq!(Arc::strong_count(&value), 2);
+        }
+
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    it "should wake up consumer when queue is closed" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(Closed));
+    }
+
+    it "should move all values from producer to consumer in order" {
+        const VALUES: i32 = 100000;
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let producer = thread::spawn(move || for i in 0..VALUES { data.enqueue(i).unwrap(); });
+
+        for i in 0..VALUES {
+            assert_eq!(arc.dequeue(), Ok(i));
+        }
+        assert!(producer.join().is_ok());
+        assert!(arc.is_empty());
+    }
+}
@@ -0,0 +1,203 @@
+pub use concrust::queue::{SynchronousQueue, BlockingQueue, Closed};
+
+pub use std::sync::Arc;
+pub use std::time::{Duration, Instant};
+
+pub use std::thread;
+
+describe! synchronous_queue_test {
+
+    before_each {
+        let queue: SynchronousQueue<i32> = SynchronousQueue::new();
+    }
+
+    it "should create unfair queue without capacity" {
+        assert!(!queue.is_fair());
+        assert!(queue.is_empty());
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.peek(), None);
+    }
+
+    it "should create fair queue" {
+        let fair: SynchronousQueue<i32> = SynchronousQueue::fair();
+
+        assert!(fair.is_fair());
+    }
+
+    it "should not offer or poll without matching thread" {
+        assert!(!queue.offer(1));
+        assert_eq!(queue.try_enqueue(2), Err(2));
+        assert_eq!(queue.poll(), None);
+    }
+
+    it "should time out without matching thread" {
+        let start = Instant::now();
+
+        assert_eq!(queue.offer_timeout(1, Duration::from_millis(50)), Err(1));
+        assert_eq!(queue.poll_timeout(Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    it "should block producer until consumer takes value" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.enqueue(1));
+
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(arc.dequeue(), Ok(1));
+        assert_eq!(jh.join().unwrap(), Ok(()));
+    }
+
+    it "should hand value to waiting consumer" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue());
+
+        thread::sleep(Duration::from_millis(100));
+
+        assert!(arc.offer(1));
+        assert_eq!(jh.join().unwrap(), Ok(1));
+    }
+
+    it "should poll value from waiting producer" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.offer_timeout(1, Duration::from_secs(5)));
+
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(arc.poll(), Some(1));
+        assert_eq!(jh.join().unwrap(), Ok(()));
+    }
+
+    it "should match the most recent producer in unfair mode" {
+        let arc = Arc::new(queue);
+        let mut handles = Vec::new();
+        for val in 1..4 {
+            let data = arc.clone();
+            handles.push(thread::spawn(move || data.enqueue(val)));
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        assert_eq!(arc.dequeue(), Ok(3));
+        assert_eq!(arc.dequeue(), Ok(2));
+        assert_eq!(arc.dequeue(), Ok(1));
+        for jh in handles {
+            assert_eq!(jh.join().unwrap(), Ok(()));
+        }
+    }
+
+    it "should match producers in FIFO order in fair mode" {
+        let arc = Arc::new(SynchronousQueue::fair());
+        let mut handles = Vec::new();
+        for val in 1..4 {
+            let data = arc.clone();
+            handles.push(thread::spawn(move || data.enqueue(val)));
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        assert_eq!(arc.dequeue(), Ok(1));
+        assert_eq!(arc.dequeue(), Ok(2));
+        assert_eq!(arc.dequeue(), Ok(3));
+        for jh in handles {
+            assert_eq!(jh.join().unwrap(), Ok(()));
+        }
+    }
+
+    it "should drain values from waiting producers" {
+        let arc = Arc::new(SynchronousQueue::fair());
+        let mut handles = Vec::new();
+        for val in 1..4 {
+            let data = arc.clone();
+            handles.push(thread::spawn(move || data.enqueue(val)));
+            thread::sleep(Duration::from_millis(50));
+        }
+        let mut target = Vec::new();
+
+        assert_eq!(arc.drain_to(&mut target, 2), 2);
+        assert_eq!(target, vec![1, 2]);
+        assert_eq!(arc.poll(), Some(3));
+        for jh in handles {
+            assert_eq!(jh.join().unwrap(), Ok(()));
+        }
+    }
+
+    it "should hand over all values" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.enqueue_all(vec![1, 2, 3]));
+
+        assert_eq!(arc.dequeue(), Ok(1));
+        assert_eq!(arc.dequeue(), Ok(2));
+        assert_eq!(arc.dequeue(), Ok(3));
+        assert_eq!(jh.join().unwrap(), Ok(()));
+    }
+
+    it "should return value back to waiting producer when closed" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.enqueue(1));
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(1));
+        assert_eq!(arc.poll(), None);
+    }
+
+    it "should wake up waiting consumer when closed" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.dequeue());
+
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(Closed));
+        assert!(arc.is_closed());
+        assert_eq!(arc.enqueue(1), Err(1));
+    }
+
+    it "should return rest of values when closed during enqueue all" {
+        let arc = Arc::new(queue);
+        let data = arc.clone();
+        let jh = thread::spawn(move || data.enqueue_all(vec![1, 2, 3]));
+
+        assert_eq!(arc.dequeue(), Ok(1));
+        thread::sleep(Duration::from_millis(100));
+        arc.close();
+
+        assert_eq!(jh.join().unwrap(), Err(vec![2, 3]));
+    }
+
+    it "should hand over every value between many producers and consumers" {
+        let arc = Arc::new(queue);
+        let mut producers = Vec::new();
+        for id in 0..4 {
+            let data = arc.clone();
+            producers.push(thread::spawn(move || {
+                for val in 0..1000 {
+                    data.enqueue(id * 1000 + val).unwrap();
+                }
+            }));
+        }
+        let mut consumers = Vec::new();
+        for _ in 0..4 {
+            let data = arc.clone();
+            consumers.push(thread::spawn(move || {
+                let mut sum = 0i64;
+                for _ in 0..1000 {
+                    sum += data.dequeue().unwrap() as i64;
+                }
+                sum
+            }));
+        }
+        for jh in producers {
+            jh.join().unwrap();
+        }
+        let sum: i64 = consumers.into_iter().map(|jh| jh.join().unwrap()).sum();
+
+        assert_eq!(sum, (0..4000).sum::<i64>());
+    }
+}